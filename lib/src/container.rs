@@ -0,0 +1,336 @@
+use std::io::{Read, Write};
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress};
+use sha2::{Digest, Sha256};
+
+use crate::{Ark, ArkCompressed, CurveCheck, DecodeError, ValidationMode};
+
+// layout of a container, all integers little-endian:
+//   magic "KZGC" | version u16 | curve u16 | kind u16 | compressed u8 | payload length u64
+//   | payload | sha256 of everything before it
+pub const MAGIC: [u8; 4] = *b"KZGC";
+pub const FORMAT_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 4 + 2 + 2 + 2 + 1 + 8;
+
+// the curve a container was written for, checked when it is read back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveId {
+    Bls12_381 = 1,
+    Bn254 = 2,
+    Bls12_377 = 3,
+    Bw6_761 = 4,
+}
+
+impl CurveId {
+    fn from_u16(id: u16) -> Option<Self> {
+        match id {
+            1 => Some(CurveId::Bls12_381),
+            2 => Some(CurveId::Bn254),
+            3 => Some(CurveId::Bls12_377),
+            4 => Some(CurveId::Bw6_761),
+            _ => None,
+        }
+    }
+}
+
+// pairings whose objects can be stored in a container
+pub trait KnownCurve: Pairing {
+    const CURVE_ID: CurveId;
+}
+
+#[cfg(feature = "bls12_381")]
+impl KnownCurve for ark_bls12_381::Bls12_381 {
+    const CURVE_ID: CurveId = CurveId::Bls12_381;
+}
+
+#[cfg(feature = "bn254")]
+impl KnownCurve for ark_bn254::Bn254 {
+    const CURVE_ID: CurveId = CurveId::Bn254;
+}
+
+#[cfg(feature = "bls12_377")]
+impl KnownCurve for ark_bls12_377::Bls12_377 {
+    const CURVE_ID: CurveId = CurveId::Bls12_377;
+}
+
+#[cfg(feature = "bw6_761")]
+impl KnownCurve for ark_bw6_761::BW6_761 {
+    const CURVE_ID: CurveId = CurveId::Bw6_761;
+}
+
+// what a container holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectKind {
+    Srs = 1,
+    VerifierKey = 2,
+    Commitment = 3,
+    Proof = 4,
+    MultiProof = 5,
+}
+
+impl ObjectKind {
+    fn from_u16(kind: u16) -> Option<Self> {
+        match kind {
+            1 => Some(ObjectKind::Srs),
+            2 => Some(ObjectKind::VerifierKey),
+            3 => Some(ObjectKind::Commitment),
+            4 => Some(ObjectKind::Proof),
+            5 => Some(ObjectKind::MultiProof),
+            _ => None,
+        }
+    }
+}
+
+// why a container could not be read
+#[derive(Debug)]
+pub enum ContainerError {
+    Io(std::io::Error),
+    // the input ended before the header, payload or checksum was complete
+    Truncated,
+    BadMagic,
+    UnsupportedVersion(u16),
+    UnknownCurve(u16),
+    UnknownKind(u16),
+    // the compressed flag is neither 0 nor 1
+    UnknownCompression(u8),
+    CurveMismatch { expected: CurveId, found: CurveId },
+    KindMismatch { expected: ObjectKind, found: ObjectKind },
+    ChecksumMismatch,
+    Decode(DecodeError),
+}
+
+impl std::fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContainerError::Io(err) => write!(f, "I/O error: {}", err),
+            ContainerError::Truncated => f.write_str("Container is truncated"),
+            ContainerError::BadMagic => f.write_str("Not a KZG container, bad magic bytes"),
+            ContainerError::UnsupportedVersion(version) => write!(f, "Unsupported container version {}", version),
+            ContainerError::UnknownCurve(id) => write!(f, "Unknown curve id {}", id),
+            ContainerError::UnknownKind(kind) => write!(f, "Unknown object kind {}", kind),
+            ContainerError::UnknownCompression(flag) => write!(f, "Unknown compression flag {}", flag),
+            ContainerError::CurveMismatch { expected, found } => {
+                write!(f, "Container holds a {:?} object, expected {:?}", found, expected)
+            }
+            ContainerError::KindMismatch { expected, found } => {
+                write!(f, "Container holds a {:?}, expected a {:?}", found, expected)
+            }
+            ContainerError::ChecksumMismatch => f.write_str("Container checksum does not match its contents"),
+            ContainerError::Decode(err) => write!(f, "Invalid payload: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {}
+
+impl From<std::io::Error> for ContainerError {
+    fn from(err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::UnexpectedEof {
+            ContainerError::Truncated
+        } else {
+            ContainerError::Io(err)
+        }
+    }
+}
+
+impl From<DecodeError> for ContainerError {
+    fn from(err: DecodeError) -> Self {
+        ContainerError::Decode(err)
+    }
+}
+
+// write value as a container of the given kind for the curve E
+pub fn write_container<E: KnownCurve, T: CanonicalSerialize, W: Write>(
+    mut writer: W,
+    kind: ObjectKind,
+    value: &T,
+    compress: Compress,
+) -> Result<(), ContainerError> {
+    let mut payload = Vec::new();
+    value
+        .serialize_with_mode(&mut payload, compress)
+        .expect("Serialization failed");
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len() + 32);
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(E::CURVE_ID as u16).to_le_bytes());
+    bytes.extend_from_slice(&(kind as u16).to_le_bytes());
+    bytes.push((compress == Compress::Yes) as u8);
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    let checksum: [u8; 32] = Sha256::digest(&bytes).into();
+    bytes.extend_from_slice(&checksum);
+
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+// read a container written by write_container, checking that it holds a kind object
+// for the curve E before decoding the payload with the given validation
+pub fn read_container<E: KnownCurve, T: CanonicalDeserialize + CurveCheck, R: Read>(
+    mut reader: R,
+    kind: ObjectKind,
+    validation: ValidationMode,
+) -> Result<T, ContainerError> {
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    if header[0..4] != MAGIC {
+        return Err(ContainerError::BadMagic);
+    }
+    let field = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+
+    let version = field(4);
+    if version != FORMAT_VERSION {
+        return Err(ContainerError::UnsupportedVersion(version));
+    }
+    let found_curve = CurveId::from_u16(field(6)).ok_or(ContainerError::UnknownCurve(field(6)))?;
+    if found_curve != E::CURVE_ID {
+        return Err(ContainerError::CurveMismatch { expected: E::CURVE_ID, found: found_curve });
+    }
+    let found_kind = ObjectKind::from_u16(field(8)).ok_or(ContainerError::UnknownKind(field(8)))?;
+    if found_kind != kind {
+        return Err(ContainerError::KindMismatch { expected: kind, found: found_kind });
+    }
+    let compress = match header[10] {
+        0 => Compress::No,
+        1 => Compress::Yes,
+        flag => return Err(ContainerError::UnknownCompression(flag)),
+    };
+    let payload_len = u64::from_le_bytes(header[11..19].try_into().unwrap());
+
+    // read through take so a corrupted length cannot allocate more than the input holds
+    let mut payload = Vec::new();
+    (&mut reader).take(payload_len).read_to_end(&mut payload)?;
+    if (payload.len() as u64) != payload_len {
+        return Err(ContainerError::Truncated);
+    }
+    let mut checksum = [0u8; 32];
+    reader.read_exact(&mut checksum)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(header);
+    hasher.update(&payload);
+    if hasher.finalize()[..] != checksum[..] {
+        return Err(ContainerError::ChecksumMismatch);
+    }
+
+    let value = if compress == Compress::Yes {
+        ArkCompressed::<T>::decode(&payload, validation)?.0
+    } else {
+        Ark::<T>::decode(&payload, validation)?.0
+    };
+    Ok(value)
+}
+
+#[cfg(all(test, feature = "bls12_381"))]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+    use ark_ec::Group;
+
+    fn points() -> Vec<G1Projective> {
+        vec![G1Projective::generator(), G1Projective::generator() * Fr::from(2u64)]
+    }
+
+    fn container(compress: Compress) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_container::<Bls12_381, _, _>(&mut bytes, ObjectKind::Commitment, &points(), compress).unwrap();
+        bytes
+    }
+
+    fn read(bytes: &[u8]) -> Result<Vec<G1Projective>, ContainerError> {
+        read_container::<Bls12_381, _, _>(bytes, ObjectKind::Commitment, ValidationMode::Full)
+    }
+
+    // overwrite part of the header and recompute the checksum so only that field is wrong
+    fn patch(mut bytes: Vec<u8>, offset: usize, field: &[u8]) -> Vec<u8> {
+        bytes[offset..offset + field.len()].copy_from_slice(field);
+        let end = bytes.len() - 32;
+        let checksum: [u8; 32] = Sha256::digest(&bytes[..end]).into();
+        bytes[end..].copy_from_slice(&checksum);
+        bytes
+    }
+
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied"))
+        }
+    }
+
+    #[test]
+    fn round_trip_in_both_modes() {
+        assert_eq!(read(&container(Compress::Yes)).unwrap(), points());
+        assert_eq!(read(&container(Compress::No)).unwrap(), points());
+    }
+
+    #[test]
+    fn rejects_io_errors_and_truncated_input() {
+        let result = read_container::<Bls12_381, Vec<G1Projective>, _>(
+            FailingReader,
+            ObjectKind::Commitment,
+            ValidationMode::Full,
+        );
+        assert!(matches!(result, Err(ContainerError::Io(_))));
+
+        let bytes = container(Compress::Yes);
+        // inside the header, inside the payload and inside the checksum
+        for len in [0, 10, HEADER_SIZE + 1, bytes.len() - 1] {
+            assert!(matches!(read(&bytes[..len]), Err(ContainerError::Truncated)));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let bytes = container(Compress::Yes);
+        assert!(matches!(read(&patch(bytes.clone(), 0, b"KZGX")), Err(ContainerError::BadMagic)));
+        assert!(matches!(
+            read(&patch(bytes.clone(), 4, &2u16.to_le_bytes())),
+            Err(ContainerError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            read(&patch(bytes.clone(), 6, &9u16.to_le_bytes())),
+            Err(ContainerError::UnknownCurve(9))
+        ));
+        assert!(matches!(
+            read(&patch(bytes.clone(), 8, &9u16.to_le_bytes())),
+            Err(ContainerError::UnknownKind(9))
+        ));
+        assert!(matches!(read(&patch(bytes, 10, &[2])), Err(ContainerError::UnknownCompression(2))));
+    }
+
+    #[test]
+    fn rejects_the_wrong_curve_or_kind() {
+        let bytes = container(Compress::Yes);
+        assert!(matches!(
+            read(&patch(bytes.clone(), 6, &(CurveId::Bn254 as u16).to_le_bytes())),
+            Err(ContainerError::CurveMismatch { expected: CurveId::Bls12_381, found: CurveId::Bn254 })
+        ));
+        assert!(matches!(
+            read_container::<Bls12_381, Vec<G1Projective>, _>(&bytes[..], ObjectKind::Proof, ValidationMode::Full),
+            Err(ContainerError::KindMismatch { expected: ObjectKind::Proof, found: ObjectKind::Commitment })
+        ));
+    }
+
+    #[test]
+    fn rejects_a_bad_checksum() {
+        let mut bytes = container(Compress::Yes);
+        bytes[HEADER_SIZE] ^= 1;
+        assert!(matches!(read(&bytes), Err(ContainerError::ChecksumMismatch)));
+    }
+
+    #[test]
+    fn rejects_a_payload_that_does_not_decode() {
+        let mut bytes = Vec::new();
+        let scalars = vec![Fr::from(1u64)];
+        write_container::<Bls12_381, _, _>(&mut bytes, ObjectKind::Commitment, &scalars, Compress::Yes).unwrap();
+        assert!(matches!(read(&bytes), Err(ContainerError::Decode(DecodeError::InvalidEncoding))));
+
+        // a valid payload with the flag flipped no longer decodes either
+        let bytes = patch(container(Compress::Yes), 10, &[0]);
+        assert!(matches!(read(&bytes), Err(ContainerError::Decode(_))));
+    }
+}
@@ -1,279 +1,686 @@
-use ark_ec::{pairing::Pairing, short_weierstrass::Projective};
-use ark_ff::MontBackend;
+// no_std with alloc so the same types serve the SP1 guest, the host and embedded verifiers,
+// the std feature only adds std::error::Error impls and the container file format
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use ark_ec::pairing::Pairing;
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ec::CurveGroup;
+use ark_ff::{Fp, FpConfig, PrimeField};
 use ark_serialize::{
-    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate, Write,
 };
-use ark_bls12_381::{Bls12_381, Config, Fr, FrConfig, G1Projective as G1, G2Projective as G2};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 
+#[cfg(feature = "std")]
+pub mod container;
 
-#[derive(Clone, Debug)]
-pub struct SerializablePoints(pub Vec<Fr>);
-impl Valid for SerializablePoints {
-    fn check(&self) -> Result<(), SerializationError> {
+
+// serde wrappers around any value arkworks can serialize, e.g. Ark<G1>, Ark<Vec<Fr>> or
+// Ark<GuestInput>. Ark encodes the uncompressed canonical serialization, which is cheapest
+// to decode in the guest. ArkCompressed encodes points compressed, for BLS12-381 the
+// standard 48 byte G1 / 96 byte G2 ZCash format also used by Ethereum, c-kzg and blst.
+// sizes follow the curve: BN254 points take 32 / 64 bytes in the little-endian arkworks
+// format, and scalars are 32 bytes on both
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ark<T>(pub T);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArkCompressed<T>(pub T);
+
+// how much checking decoding does: Full checks that every point is on the curve and in
+// the prime order subgroup, Trusted skips that for inputs from a trusted source.
+// field elements are rejected unless canonical in both modes, arkworks checks them while reading
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationMode {
+    Full,
+    Trusted,
+}
+
+// why bytes could not be decoded into a value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    // truncated input, unexpected flags or tags, or a non-canonical field element
+    InvalidEncoding,
+    // the input continues after the encoded value
+    TrailingBytes,
+    // a human-readable encoding that is not a 0x-prefixed string of hex digit pairs
+    InvalidHex,
+    NotOnCurve,
+    NotInSubgroup,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            DecodeError::InvalidEncoding => "Invalid encoding",
+            DecodeError::TrailingBytes => "Trailing bytes after the encoded value",
+            DecodeError::InvalidHex => "Invalid 0x-prefixed hex string",
+            DecodeError::NotOnCurve => "Point is not on the curve",
+            DecodeError::NotInSubgroup => "Point is not in the prime order subgroup",
+        };
+        f.write_str(message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+// the checks arkworks leaves out of decoding: the BLS12-381 decoders never check that an
+// uncompressed point is on the curve, and without validation not the subgroup either
+pub trait CurveCheck {
+    fn curve_check(&self) -> Result<(), DecodeError>;
+}
+
+impl<P: SWCurveConfig> CurveCheck for Projective<P> {
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        let affine = self.into_affine();
+        if !affine.is_on_curve() {
+            return Err(DecodeError::NotOnCurve);
+        }
+        if !affine.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(DecodeError::NotInSubgroup);
+        }
         Ok(())
     }
 }
 
-impl CanonicalSerialize for SerializablePoints {
-    fn serialize_with_mode<W: std::io::Write>(
-        &self,
-        writer: W,
-        compress: Compress,
-    ) -> Result<(), SerializationError> {
-        self.0.serialize_with_mode(writer, compress)
+impl<P: FpConfig<N>, const N: usize> CurveCheck for Fp<P, N> {
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        Ok(())
     }
+}
 
-    fn serialized_size(&self, compress: Compress) -> usize {
-        self.0.serialized_size(compress)
+impl<T: CurveCheck> CurveCheck for Vec<T> {
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.iter().try_for_each(CurveCheck::curve_check)
     }
 }
 
-impl CanonicalDeserialize for SerializablePoints {
-    fn deserialize_with_mode<R: std::io::Read>(
-        reader: R,
-        compress: Compress,
-        validate: Validate,
-    ) -> Result<Self, SerializationError> {
-        Ok(SerializablePoints(Vec::deserialize_with_mode(
-            reader, compress, validate,
-        )?))
+// to_bytes / decode and the serde implementations of a wrapper, with the given compression
+macro_rules! impl_ark_wrapper {
+    ($wrapper:ident, $compress:expr) => {
+        impl<T> From<T> for $wrapper<T> {
+            fn from(value: T) -> Self {
+                $wrapper(value)
+            }
+        }
+
+        impl<T: CanonicalSerialize> $wrapper<T> {
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut serialized_data = Vec::new();
+                self.0
+                    .serialize_with_mode(&mut serialized_data, $compress)
+                    .expect("Serialization failed");
+                serialized_data
+            }
+        }
+
+        impl<T: CanonicalDeserialize + CurveCheck> $wrapper<T> {
+            pub fn decode(bytes: &[u8], validation: ValidationMode) -> Result<Self, DecodeError> {
+                let mut reader = bytes;
+                let value = T::deserialize_with_mode(&mut reader, $compress, Validate::No)
+                    .map_err(|_| DecodeError::InvalidEncoding)?;
+                if !reader.is_empty() {
+                    return Err(DecodeError::TrailingBytes);
+                }
+                if validation == ValidationMode::Full {
+                    value.curve_check()?;
+                }
+                Ok($wrapper(value))
+            }
+        }
+
+        // decoding with every check, use decode to skip them for trusted inputs
+        impl<T: CanonicalDeserialize + CurveCheck> TryFrom<&[u8]> for $wrapper<T> {
+            type Error = DecodeError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, DecodeError> {
+                $wrapper::decode(bytes, ValidationMode::Full)
+            }
+        }
+
+        // a 0x-prefixed hex string in human-readable formats such as JSON, raw bytes otherwise
+        impl<T: CanonicalSerialize> Serialize for $wrapper<T> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    serializer.serialize_str(&to_hex(&self.to_bytes()))
+                } else {
+                    serializer.serialize_bytes(&self.to_bytes())
+                }
+            }
+        }
+
+        impl<'de, T: CanonicalDeserialize + CurveCheck> Deserialize<'de> for $wrapper<T> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let serialized_data = if deserializer.is_human_readable() {
+                    from_hex(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)?
+                } else {
+                    serde_bytes::ByteBuf::deserialize(deserializer)?.into_vec()
+                };
+                $wrapper::try_from(&serialized_data[..]).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+impl_ark_wrapper!(Ark, Compress::No);
+impl_ark_wrapper!(ArkCompressed, Compress::Yes);
+
+// lowercase hex with a 0x prefix
+pub fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(2 + 2 * bytes.len());
+    hex.push_str("0x");
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize] as char);
+        hex.push(DIGITS[(byte & 0x0f) as usize] as char);
     }
+    hex
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SerdeSerializablePoints(#[serde(with = "serde_bytes")] pub Vec<u8>);
-impl From<SerializablePoints> for SerdeSerializablePoints {
-    fn from(points: SerializablePoints) -> Self {
-        let mut serialized_data = Vec::new();
-        points
-            .serialize_uncompressed(&mut serialized_data)
-            .expect("Serialization failed");
-        SerdeSerializablePoints(serialized_data)
+// inverse of to_hex, accepting either case
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits = hex.strip_prefix("0x").ok_or(DecodeError::InvalidHex)?.as_bytes();
+    if digits.len() % 2 != 0 {
+        return Err(DecodeError::InvalidHex);
     }
+    let nibble = |digit: u8| (digit as char).to_digit(16).ok_or(DecodeError::InvalidHex);
+    digits
+        .chunks(2)
+        .map(|pair| Ok(((nibble(pair[0])? << 4) | nibble(pair[1])?) as u8))
+        .collect()
 }
 
-impl From<SerdeSerializablePoints> for SerializablePoints {
-    fn from(points: SerdeSerializablePoints) -> Self {
-        SerializablePoints::deserialize_uncompressed(&mut &points.0[..])
-            .expect("Deserialization failed")
+// the KZG data exchanged as files or over APIs, points use the compressed encoding.
+// in JSON every value is a hex string, see the schemas in lib/schemas for the sizes per curve
+
+// an opening of a commitment at a single point
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound(serialize = "", deserialize = "E::G1: CurveCheck, E::ScalarField: CurveCheck"))]
+pub struct ProofFile<E: Pairing> {
+    pub commitment: ArkCompressed<E::G1>,
+    pub point: Ark<E::ScalarField>,
+    pub value: Ark<E::ScalarField>,
+    pub proof: ArkCompressed<E::G1>,
+}
+
+// an opening of a commitment at several points with a single proof
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound(serialize = "", deserialize = "E::G1: CurveCheck, E::ScalarField: CurveCheck"))]
+pub struct MultiProofFile<E: Pairing> {
+    pub commitment: ArkCompressed<E::G1>,
+    pub points: Vec<Ark<E::ScalarField>>,
+    pub values: Vec<Ark<E::ScalarField>>,
+    pub proof: ArkCompressed<E::G1>,
+}
+
+// the powers of tau of a trusted setup, crs_g1[i] = tau^i * g1 and crs_g2[i] = tau^i * g2
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound(serialize = "", deserialize = "E::G1: CurveCheck, E::G2: CurveCheck"))]
+pub struct SrsFile<E: Pairing> {
+    pub g1: ArkCompressed<E::G1>,
+    pub g2: ArkCompressed<E::G2>,
+    pub g2_tau: ArkCompressed<E::G2>,
+    pub crs_g1: Vec<ArkCompressed<E::G1>>,
+    pub crs_g2: Vec<ArkCompressed<E::G2>>,
+}
+
+// a whole trusted setup of any pairing, stored in an ObjectKind::Srs container
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SerializableSrs<E: Pairing> {
+    pub g1: E::G1,
+    pub g2: E::G2,
+    pub g2_tau: E::G2,
+    pub crs_g1: Vec<E::G1>,
+    pub crs_g2: Vec<E::G2>,
+}
+
+impl<E: Pairing> CurveCheck for SerializableSrs<E>
+where
+    E::G1: CurveCheck,
+    E::G2: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.g1.curve_check()?;
+        self.g2.curve_check()?;
+        self.g2_tau.curve_check()?;
+        self.crs_g1.curve_check()?;
+        self.crs_g2.curve_check()
     }
 }
 
+// the generators, g2_tau and a crs_g1 prefix, everything the guest needs to check a multi-proof.
+// crs_g1_rest is the crs_digest of the powers past the prefix, EMPTY_CRS_DIGEST if there are
+// none, so that setup_digest identifies the whole setup whatever prefix is sent
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SerializableVerifierKey<E: Pairing> {
+    pub g1: E::G1,
+    pub g2: E::G2,
+    pub g2_tau: E::G2,
+    pub crs_g1: Vec<E::G1>,
+    pub crs_g1_rest: [u8; 32],
+}
+
+// a multi-opening checked with pairings: the statement (commitment, points, values),
+// its proof pi and the zero commitment / Lagrange polynomial hints
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SerializableMultiOpening<E: Pairing> {
+    pub commitment: E::G1,
+    pub points: Vec<E::ScalarField>,
+    pub values: Vec<E::ScalarField>,
+    pub pi: E::G1,
+    pub zero_commitment: E::G2,
+    pub lagrange_poly: Vec<E::ScalarField>,
+}
+
+// a multi-opening checked with the quotient identity: the statement,
+// the committed polynomial and the quotient (poly - lagrange) / zero_poly
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SerializableQuotientOpening<E: Pairing> {
+    pub commitment: E::G1,
+    pub points: Vec<E::ScalarField>,
+    pub values: Vec<E::ScalarField>,
+    pub poly: Vec<E::ScalarField>,
+    pub quotient: Vec<E::ScalarField>,
+}
 
+// what the guest is asked to check, one variant per VerificationMode
 #[derive(Clone, Debug)]
-pub struct SerializablePolynomial(pub Vec<ark_ff::Fp<ark_ff::MontBackend<ark_bls12_381::FrConfig, 4>, 4>> );
-impl Valid for SerializablePolynomial {
+pub enum GuestStatement<E: Pairing> {
+    Pairing(SerializableMultiOpening<E>),
+    QuotientCheck(SerializableQuotientOpening<E>),
+    Batch(Vec<SerializableMultiOpening<E>>),
+}
+
+impl<E: Pairing> GuestStatement<E> {
+    pub fn mode(&self) -> VerificationMode {
+        match self {
+            GuestStatement::Pairing(_) => VerificationMode::Pairing,
+            GuestStatement::QuotientCheck(_) => VerificationMode::QuotientCheck,
+            GuestStatement::Batch(_) => VerificationMode::Batch,
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            GuestStatement::Pairing(_) => 0,
+            GuestStatement::QuotientCheck(_) => 1,
+            GuestStatement::Batch(_) => 2,
+        }
+    }
+}
+
+impl<E: Pairing> Valid for GuestStatement<E> {
     fn check(&self) -> Result<(), SerializationError> {
-        Ok(())
+        match self {
+            GuestStatement::Pairing(opening) => opening.check(),
+            GuestStatement::QuotientCheck(opening) => opening.check(),
+            GuestStatement::Batch(openings) => openings.check(),
+        }
     }
 }
 
-impl CanonicalSerialize for SerializablePolynomial {
-    fn serialize_with_mode<W: std::io::Write>(
+// a one byte tag followed by the variant
+impl<E: Pairing> CanonicalSerialize for GuestStatement<E> {
+    fn serialize_with_mode<W: Write>(
         &self,
-        writer: W,
+        mut writer: W,
         compress: Compress,
     ) -> Result<(), SerializationError> {
-        self.0.serialize_with_mode(writer, compress)
+        self.tag().serialize_with_mode(&mut writer, compress)?;
+        match self {
+            GuestStatement::Pairing(opening) => opening.serialize_with_mode(writer, compress),
+            GuestStatement::QuotientCheck(opening) => opening.serialize_with_mode(writer, compress),
+            GuestStatement::Batch(openings) => openings.serialize_with_mode(writer, compress),
+        }
     }
 
     fn serialized_size(&self, compress: Compress) -> usize {
-        self.0.serialized_size(compress)
+        self.tag().serialized_size(compress)
+            + match self {
+                GuestStatement::Pairing(opening) => opening.serialized_size(compress),
+                GuestStatement::QuotientCheck(opening) => opening.serialized_size(compress),
+                GuestStatement::Batch(openings) => openings.serialized_size(compress),
+            }
     }
 }
 
-impl CanonicalDeserialize for SerializablePolynomial {
-    fn deserialize_with_mode<R: std::io::Read>(
-        reader: R,
+impl<E: Pairing> CanonicalDeserialize for GuestStatement<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
         compress: Compress,
         validate: Validate,
     ) -> Result<Self, SerializationError> {
-        Ok(SerializablePolynomial(Vec::deserialize_with_mode(
-            reader, compress, validate,
-        )?))
+        match u8::deserialize_with_mode(&mut reader, compress, validate)? {
+            0 => Ok(GuestStatement::Pairing(SerializableMultiOpening::deserialize_with_mode(
+                reader, compress, validate,
+            )?)),
+            1 => Ok(GuestStatement::QuotientCheck(SerializableQuotientOpening::deserialize_with_mode(
+                reader, compress, validate,
+            )?)),
+            2 => Ok(GuestStatement::Batch(Vec::<SerializableMultiOpening<E>>::deserialize_with_mode(
+                reader, compress, validate,
+            )?)),
+            _ => Err(SerializationError::InvalidData),
+        }
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SerdeSerializablePolynomial(#[serde(with = "serde_bytes")] pub Vec<u8>);
-impl From<SerializablePolynomial> for SerdeSerializablePolynomial {
-    fn from(points: SerializablePolynomial) -> Self {
-        let mut serialized_data = Vec::new();
-        points
-            .serialize_uncompressed(&mut serialized_data)
-            .expect("Serialization failed");
-        SerdeSerializablePolynomial(serialized_data)
-    }
+// version of the GuestInput layout, bumped whenever a field changes
+pub const GUEST_INPUT_VERSION: u32 = 2;
+
+// everything the guest reads, written by the host with a single stdin.write
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GuestInput<E: Pairing> {
+    pub version: u32,
+    pub verifier_key: SerializableVerifierKey<E>,
+    pub statement: GuestStatement<E>,
 }
 
-impl From<SerdeSerializablePolynomial> for SerializablePolynomial {
-    fn from(points: SerdeSerializablePolynomial) -> Self {
-        SerializablePolynomial::deserialize_uncompressed(&mut &points.0[..])
-            .expect("Deserialization failed")
+impl<E: Pairing> GuestInput<E> {
+    pub fn new(verifier_key: SerializableVerifierKey<E>, statement: GuestStatement<E>) -> Self {
+        GuestInput {
+            version: GUEST_INPUT_VERSION,
+            verifier_key,
+            statement,
+        }
+    }
+
+    // check the shape of every field before the guest starts verifying,
+    // the cryptographic checks are left to the verifier
+    pub fn validate(&self) -> Result<(), InputError> {
+        if self.version != GUEST_INPUT_VERSION {
+            return Err(InputError::UnsupportedVersion(self.version));
+        }
+        if self.verifier_key.crs_g1.is_empty() {
+            return Err(InputError::EmptyVerifierKey);
+        }
+        let max_points = self.verifier_key.crs_g1.len() - 1;
+
+        match &self.statement {
+            GuestStatement::Pairing(opening) => validate_multi_opening(opening, max_points),
+            GuestStatement::QuotientCheck(opening) => {
+                validate_statement(&opening.points, &opening.values)?;
+                if degree_bound(&opening.poly) > self.verifier_key.crs_g1.len() {
+                    return Err(InputError::DegreeTooLarge);
+                }
+                Ok(())
+            }
+            GuestStatement::Batch(openings) => {
+                if openings.is_empty() {
+                    return Err(InputError::EmptyBatch);
+                }
+                openings
+                    .iter()
+                    .try_for_each(|opening| validate_multi_opening(opening, max_points))
+            }
+        }
     }
 }
 
+// why a GuestInput has the wrong shape
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputError {
+    // written for another GUEST_INPUT_VERSION
+    UnsupportedVersion(u32),
+    EmptyVerifierKey,
+    NoPoints,
+    LengthMismatch,
+    // more points than the crs_g1 prefix of the verifier key supports
+    TooManyPoints,
+    // a quotient check polynomial longer than the crs_g1 prefix of the verifier key
+    DegreeTooLarge,
+    // a Lagrange polynomial hint of degree at least the number of points
+    LagrangeTooLarge,
+    EmptyBatch,
+}
 
-#[derive(Clone, Debug)]
-pub struct SerializableLagrangePolynomial(pub Vec<<Bls12_381 as Pairing>::ScalarField>);
-// Vec<ark_ff::Fp<ark_ff::MontBackend<ark_bls12_381::FrConfig, 4>, 4>>
-impl Valid for SerializableLagrangePolynomial {
-    fn check(&self) -> Result<(), SerializationError> {
-        Ok(())
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::UnsupportedVersion(version) => write!(f, "Unsupported guest input version {}", version),
+            InputError::EmptyVerifierKey => f.write_str("Verifier key has an empty crs_g1"),
+            InputError::NoPoints => f.write_str("No points given"),
+            InputError::LengthMismatch => f.write_str("Number of points and values do not match"),
+            InputError::TooManyPoints => f.write_str("Number of points exceeds the verifier key"),
+            InputError::DegreeTooLarge => f.write_str("Polynomial degree exceeds the verifier key"),
+            InputError::LagrangeTooLarge => f.write_str("Lagrange polynomial degree exceeds the number of points"),
+            InputError::EmptyBatch => f.write_str("Batch has no openings"),
+        }
     }
 }
 
-impl CanonicalSerialize for SerializableLagrangePolynomial {
-    fn serialize_with_mode<W: std::io::Write>(
-        &self,
-        writer: W,
-        compress: Compress,
-    ) -> Result<(), SerializationError> {
-        self.0.serialize_with_mode(writer, compress)
-    }
+#[cfg(feature = "std")]
+impl std::error::Error for InputError {}
 
-    fn serialized_size(&self, compress: Compress) -> usize {
-        self.0.serialized_size(compress)
+fn validate_statement<F: PrimeField>(points: &[F], values: &[F]) -> Result<(), InputError> {
+    if points.is_empty() {
+        return Err(InputError::NoPoints);
     }
+    if points.len() != values.len() {
+        return Err(InputError::LengthMismatch);
+    }
+    Ok(())
 }
 
-impl CanonicalDeserialize for SerializableLagrangePolynomial {
-    fn deserialize_with_mode<R: std::io::Read>(
-        reader: R,
-        compress: Compress,
-        validate: Validate,
-    ) -> Result<Self, SerializationError> {
-        Ok(SerializableLagrangePolynomial(Vec::deserialize_with_mode(
-            reader, compress, validate,
-        )?))
+fn validate_multi_opening<E: Pairing>(opening: &SerializableMultiOpening<E>, max_points: usize) -> Result<(), InputError> {
+    validate_statement(&opening.points, &opening.values)?;
+    if opening.points.len() > max_points {
+        return Err(InputError::TooManyPoints);
+    }
+    if degree_bound(&opening.lagrange_poly) > opening.points.len() {
+        return Err(InputError::LagrangeTooLarge);
     }
+    Ok(())
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SerdeSerializableLagrangePolynomial(#[serde(with = "serde_bytes")] pub Vec<u8>);
-impl From<SerializableLagrangePolynomial> for SerdeSerializableLagrangePolynomial {
-    fn from(points: SerializableLagrangePolynomial) -> Self {
-        let mut serialized_data = Vec::new();
-        points
-            .serialize_uncompressed(&mut serialized_data)
-            .expect("Serialization failed");
-        SerdeSerializableLagrangePolynomial(serialized_data)
-    }
+// number of coefficients once trailing zeros are ignored
+fn degree_bound<F: PrimeField>(poly: &[F]) -> usize {
+    poly.iter().rposition(|coeff| !coeff.is_zero()).map_or(0, |i| i + 1)
 }
 
-impl From<SerdeSerializableLagrangePolynomial> for SerializableLagrangePolynomial {
-    fn from(points: SerdeSerializableLagrangePolynomial) -> Self {
-        SerializableLagrangePolynomial::deserialize_uncompressed(&mut &points.0[..])
-            .expect("Deserialization failed")
+impl<E: Pairing> CurveCheck for SerializableVerifierKey<E>
+where
+    E::G1: CurveCheck,
+    E::G2: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.g1.curve_check()?;
+        self.g2.curve_check()?;
+        self.g2_tau.curve_check()?;
+        self.crs_g1.curve_check()
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct SerializableCommitment(pub ark_ec::short_weierstrass::Projective<ark_bls12_381::g1::Config>);
-impl Valid for SerializableCommitment {
-    fn check(&self) -> Result<(), SerializationError> {
-        Ok(())
+impl<E: Pairing> CurveCheck for SerializableMultiOpening<E>
+where
+    E::G1: CurveCheck,
+    E::G2: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.commitment.curve_check()?;
+        self.pi.curve_check()?;
+        self.zero_commitment.curve_check()
     }
 }
 
-impl CanonicalSerialize for SerializableCommitment {
-    fn serialize_with_mode<W: std::io::Write>(
-        &self,
-        writer: W,
-        compress: Compress,
-    ) -> Result<(), SerializationError> {
-        self.0.serialize_with_mode(writer, compress)
+impl<E: Pairing> CurveCheck for SerializableQuotientOpening<E>
+where
+    E::G1: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.commitment.curve_check()
     }
+}
 
-    fn serialized_size(&self, compress: Compress) -> usize {
-        self.0.serialized_size(compress)
+impl<E: Pairing> CurveCheck for GuestStatement<E>
+where
+    E::G1: CurveCheck,
+    E::G2: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        match self {
+            GuestStatement::Pairing(opening) => opening.curve_check(),
+            GuestStatement::QuotientCheck(opening) => opening.curve_check(),
+            GuestStatement::Batch(openings) => openings.curve_check(),
+        }
     }
 }
 
-impl CanonicalDeserialize for SerializableCommitment {
-    fn deserialize_with_mode<R: std::io::Read>(
-        reader: R,
-        compress: Compress,
-        validate: Validate,
-    ) -> Result<Self, SerializationError> {
-        Ok(SerializableCommitment(ark_ec::short_weierstrass::Projective::<ark_bls12_381::g1::Config>::deserialize_with_mode(
-            reader, compress, validate,
-        )?))
+impl<E: Pairing> CurveCheck for GuestInput<E>
+where
+    E::G1: CurveCheck,
+    E::G2: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.verifier_key.curve_check()?;
+        self.statement.curve_check()
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SerdeSerializableCommitment(#[serde(with = "serde_bytes")] pub Vec<u8>);
-impl From<SerializableCommitment> for SerdeSerializableCommitment {
-    fn from(points: SerializableCommitment) -> Self {
+// crs_digest of no powers
+pub const EMPTY_CRS_DIGEST: [u8; 32] = [0u8; 32];
+
+// digest of a list of powers chained from the last one, sha256(crs[0] || crs_digest(crs[1..]))
+// with rest as the digest past the end. the digest of a prefix given the digest of the
+// remaining powers as rest is the digest of the whole list
+pub fn crs_digest<G: CanonicalSerialize>(crs: &[G], rest: [u8; 32]) -> [u8; 32] {
+    crs.iter().rev().fold(rest, |rest, power| {
         let mut serialized_data = Vec::new();
-        points
+        power
             .serialize_uncompressed(&mut serialized_data)
             .expect("Serialization failed");
-        SerdeSerializableCommitment(serialized_data)
-    }
+        serialized_data.extend_from_slice(&rest);
+        Sha256::digest(&serialized_data).into()
+    })
 }
 
-impl From<SerdeSerializableCommitment> for SerializableCommitment {
-    fn from(points: SerdeSerializableCommitment) -> Self {
-        SerializableCommitment::deserialize_uncompressed(&mut &points.0[..])
-            .expect("Deserialization failed")
-    }
+// sha256 over the generators, g2_tau and the crs_digest of the whole crs_g1, pins proofs to
+// one trusted setup independently of the crs_g1 prefix the verifier key carries
+pub fn setup_digest<E: Pairing>(vk: &SerializableVerifierKey<E>) -> [u8; 32] {
+    let mut serialized_data = Vec::new();
+    vk.g1
+        .serialize_uncompressed(&mut serialized_data)
+        .expect("Serialization failed");
+    vk.g2
+        .serialize_uncompressed(&mut serialized_data)
+        .expect("Serialization failed");
+    vk.g2_tau
+        .serialize_uncompressed(&mut serialized_data)
+        .expect("Serialization failed");
+    serialized_data.extend_from_slice(&crs_digest(&vk.crs_g1, vk.crs_g1_rest));
+    Sha256::digest(&serialized_data).into()
 }
 
-#[derive(Clone, Debug)]
-pub struct SerializableG2Commitment(pub ark_ec::short_weierstrass::Projective<ark_bls12_381::g2::Config>);
-impl Valid for SerializableG2Commitment {
-    fn check(&self) -> Result<(), SerializationError> {
-        Ok(())
-    }
+// the public values committed by the guest, whatever statement it was given. the variant
+// comes first in the encoding, so a decoder learns from the public values themselves which
+// kind of statement was proven
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "E::G1: CurveCheck"))]
+pub enum GuestOutput<E: Pairing> {
+    // a single statement, checked in the Pairing or QuotientCheck mode
+    Statement(StatementOutput<E>),
+    // a batch of statements, checked in the Batch mode
+    Batch(BatchOutput),
 }
 
-impl CanonicalSerialize for SerializableG2Commitment {
-    fn serialize_with_mode<W: std::io::Write>(
-        &self,
-        writer: W,
-        compress: Compress,
-    ) -> Result<(), SerializationError> {
-        self.0.serialize_with_mode(writer, compress)
+impl<E: Pairing> GuestOutput<E> {
+    pub fn mode(&self) -> VerificationMode {
+        match self {
+            GuestOutput::Statement(output) => output.mode,
+            GuestOutput::Batch(_) => VerificationMode::Batch,
+        }
     }
+}
 
-    fn serialized_size(&self, compress: Compress) -> usize {
-        self.0.serialized_size(compress)
-    }
+// the outcome of a single statement: the setup and statement that were checked, the
+// number of crs_g1 powers used, the mode they were checked in and whether the statement
+// holds. a downstream verifier recomputes the digests from the setup, points and values
+// it cares about
+#[derive(Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "E::G1: CurveCheck"))]
+pub struct StatementOutput<E: Pairing> {
+    pub setup_digest: [u8; 32],
+    pub crs_g1_len: u64,
+    pub commitment: ArkCompressed<E::G1>,
+    pub statement_digest: [u8; 32],
+    pub mode: VerificationMode,
+    pub verified: bool,
 }
 
-impl CanonicalDeserialize for SerializableG2Commitment {
-    fn deserialize_with_mode<R: std::io::Read>(
-        reader: R,
-        compress: Compress,
-        validate: Validate,
-    ) -> Result<Self, SerializationError> {
-        Ok(SerializableG2Commitment(ark_ec::short_weierstrass::Projective::<ark_bls12_381::g2::Config>::deserialize_with_mode(
-            reader, compress, validate,
-        )?))
-    }
+// how the guest checked its statement, see GuestStatement
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationMode {
+    // pairing check of a multi-proof pi, with the zero commitment and Lagrange polynomial as hints
+    Pairing,
+    // field only check of p - I == q * Z at a random point, with p and the quotient q as hints
+    QuotientCheck,
+    // several pairing mode openings folded into one pairing product with random coefficients,
+    // the guest commits a GuestOutput::Batch
+    Batch,
+}
+
+// sha256 over the serialized points followed by the serialized values
+pub fn statement_digest<F: PrimeField>(points: &[F], values: &[F]) -> [u8; 32] {
+    let mut serialized_data = Vec::new();
+    points
+        .serialize_uncompressed(&mut serialized_data)
+        .expect("Serialization failed");
+    values
+        .serialize_uncompressed(&mut serialized_data)
+        .expect("Serialization failed");
+    Sha256::digest(&serialized_data).into()
 }
 
+// the outcome of a batch: the setup and number of crs_g1 powers as in StatementOutput,
+// the number of statements, the Merkle root over their leaves (see statement_leaf) and
+// whether all of them hold
 #[derive(Serialize, Deserialize)]
-pub struct SerdeSerializableG2Commitment(#[serde(with = "serde_bytes")] pub Vec<u8>);
-impl From<SerializableG2Commitment> for SerdeSerializableG2Commitment {
-    fn from(points: SerializableG2Commitment) -> Self {
-        let mut serialized_data = Vec::new();
-        points
-            .serialize_uncompressed(&mut serialized_data)
-            .expect("Serialization failed");
-        SerdeSerializableG2Commitment(serialized_data)
-    }
+pub struct BatchOutput {
+    pub setup_digest: [u8; 32],
+    pub crs_g1_len: u64,
+    pub statement_count: u64,
+    pub statements_root: [u8; 32],
+    pub verified: bool,
 }
 
-impl From<SerdeSerializableG2Commitment> for SerializableG2Commitment {
-    fn from(points: SerdeSerializableG2Commitment) -> Self {
-        SerializableG2Commitment::deserialize_uncompressed(&mut &points.0[..])
-            .expect("Deserialization failed")
+// Merkle leaf of one statement, sha256(0x00 || commitment || statement_digest(points, values))
+// with the commitment in its standard compressed encoding
+pub fn statement_leaf<G: CurveGroup>(commitment: &G, points: &[G::ScalarField], values: &[G::ScalarField]) -> [u8; 32] {
+    let mut serialized_data = vec![0u8];
+    commitment
+        .serialize_compressed(&mut serialized_data)
+        .expect("Serialization failed");
+    serialized_data.extend_from_slice(&statement_digest(points, values));
+    Sha256::digest(&serialized_data).into()
+}
+
+// Merkle root over the leaves, padded with zero leaves to a power of two.
+// inner nodes are sha256(0x01 || left || right) so they cannot be taken for leaves
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    level.resize(leaves.len().max(1).next_power_of_two(), [0u8; 32]);
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut hasher = Sha256::new();
+                hasher.update([1u8]);
+                hasher.update(pair[0]);
+                hasher.update(pair[1]);
+                hasher.finalize().into()
+            })
+            .collect();
     }
+    level[0]
 }
 
 
@@ -487,4 +894,308 @@ impl From<SerdeSerializableG2Commitment> for SerializableG2Commitment {
 //         SerializablePreparedVerifyingKey::deserialize_uncompressed(&mut &vk.0[..])
 //             .expect("Deserialization failed")
 //     }
-// }
\ No newline at end of file
+// }
+
+#[cfg(all(test, feature = "bls12_381"))]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fq, Fr, G1Affine, G1Projective, G2Projective};
+    use ark_ec::Group;
+    use ark_ff::BigInteger;
+    use ark_std::Zero;
+
+    // the compressed generators of the ZCash serialization spec, as used by c-kzg and blst
+    const G1_GENERATOR: &str = "0x97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+    const G2_GENERATOR: &str = "0x93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8";
+
+    // the point at infinity sets the compression and infinity flags of an otherwise zero encoding
+    fn infinity(len: usize) -> Vec<u8> {
+        let mut bytes = vec![0u8; len];
+        bytes[0] = 0xc0;
+        bytes
+    }
+
+    #[test]
+    fn compressed_generators_match_known_answers() {
+        assert_eq!(to_hex(&ArkCompressed(G1Projective::generator()).to_bytes()), G1_GENERATOR);
+        assert_eq!(to_hex(&ArkCompressed(G2Projective::generator()).to_bytes()), G2_GENERATOR);
+    }
+
+    #[test]
+    fn compressed_infinity_matches_known_answers() {
+        assert_eq!(ArkCompressed(G1Projective::zero()).to_bytes(), infinity(48));
+        assert_eq!(ArkCompressed(G2Projective::zero()).to_bytes(), infinity(96));
+    }
+
+    #[test]
+    fn known_answers_decode() {
+        let g1 = ArkCompressed::<G1Projective>::try_from(&from_hex(G1_GENERATOR).unwrap()[..]).unwrap();
+        assert_eq!(g1.0, G1Projective::generator());
+        let g2 = ArkCompressed::<G2Projective>::try_from(&from_hex(G2_GENERATOR).unwrap()[..]).unwrap();
+        assert_eq!(g2.0, G2Projective::generator());
+
+        let g1 = ArkCompressed::<G1Projective>::try_from(&infinity(48)[..]).unwrap();
+        assert!(g1.0.is_zero());
+        let g2 = ArkCompressed::<G2Projective>::try_from(&infinity(96)[..]).unwrap();
+        assert!(g2.0.is_zero());
+    }
+
+    #[test]
+    fn merkle_root_pads_with_zero_leaves() {
+        let leaf = |byte: u8| [byte; 32];
+        let node = |left: [u8; 32], right: [u8; 32]| -> [u8; 32] {
+            let mut hasher = Sha256::new();
+            hasher.update([1u8]);
+            hasher.update(left);
+            hasher.update(right);
+            hasher.finalize().into()
+        };
+
+        assert_eq!(merkle_root(&[]), [0u8; 32]);
+        assert_eq!(merkle_root(&[leaf(1)]), leaf(1));
+        assert_eq!(merkle_root(&[leaf(1), leaf(2)]), node(leaf(1), leaf(2)));
+        // an odd count is padded to the next power of two
+        assert_eq!(
+            merkle_root(&[leaf(1), leaf(2), leaf(3)]),
+            node(node(leaf(1), leaf(2)), node(leaf(3), [0u8; 32]))
+        );
+        assert_ne!(merkle_root(&[leaf(1), leaf(2), leaf(3)]), merkle_root(&[leaf(1), leaf(3), leaf(2)]));
+    }
+
+    #[test]
+    fn statement_leaf_binds_the_statement() {
+        let commitment = G1Projective::generator();
+        let points = [Fr::from(1u64), Fr::from(2u64)];
+        let values = [Fr::from(3u64), Fr::from(4u64)];
+
+        let mut expected = vec![0u8];
+        expected.extend_from_slice(&ArkCompressed(commitment).to_bytes());
+        expected.extend_from_slice(&statement_digest(&points, &values));
+        let expected: [u8; 32] = Sha256::digest(&expected).into();
+        assert_eq!(statement_leaf(&commitment, &points, &values), expected);
+
+        assert_ne!(statement_leaf(&(commitment + commitment), &points, &values), expected);
+        assert_ne!(statement_leaf(&commitment, &values, &points), expected);
+        assert_ne!(statement_leaf(&commitment, &points, &[values[0], values[0]]), expected);
+    }
+
+    fn guest_input(points: usize, lagrange_len: usize) -> GuestInput<Bls12_381> {
+        let g1 = G1Projective::generator();
+        let g2 = G2Projective::generator();
+        let verifier_key = SerializableVerifierKey {
+            g1,
+            g2,
+            g2_tau: g2,
+            crs_g1: vec![g1; 5],
+            crs_g1_rest: EMPTY_CRS_DIGEST,
+        };
+        let opening = SerializableMultiOpening {
+            commitment: g1,
+            points: vec![Fr::from(1u64); points],
+            values: vec![Fr::from(2u64); points],
+            pi: g1,
+            zero_commitment: g2,
+            lagrange_poly: vec![Fr::from(3u64); lagrange_len],
+        };
+        GuestInput::new(verifier_key, GuestStatement::Pairing(opening))
+    }
+
+    #[test]
+    fn validate_accepts_a_well_formed_input() {
+        assert_eq!(guest_input(4, 4).validate(), Ok(()));
+        // trailing zeros of the Lagrange hint do not count towards its degree
+        let mut input = guest_input(4, 4);
+        if let GuestStatement::Pairing(opening) = &mut input.statement {
+            opening.lagrange_poly.extend([Fr::zero(); 3]);
+        }
+        assert_eq!(input.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_malformed_inputs() {
+        let mut input = guest_input(4, 4);
+        input.version = GUEST_INPUT_VERSION + 1;
+        assert_eq!(input.validate(), Err(InputError::UnsupportedVersion(GUEST_INPUT_VERSION + 1)));
+
+        let mut input = guest_input(4, 4);
+        input.verifier_key.crs_g1.clear();
+        assert_eq!(input.validate(), Err(InputError::EmptyVerifierKey));
+
+        assert_eq!(guest_input(0, 0).validate(), Err(InputError::NoPoints));
+        // the verifier key holds 5 powers, enough for 4 points
+        assert_eq!(guest_input(5, 5).validate(), Err(InputError::TooManyPoints));
+        assert_eq!(guest_input(4, 5).validate(), Err(InputError::LagrangeTooLarge));
+
+        let mut input = guest_input(4, 4);
+        if let GuestStatement::Pairing(opening) = &mut input.statement {
+            opening.values.pop();
+        }
+        assert_eq!(input.validate(), Err(InputError::LengthMismatch));
+
+        let mut input = guest_input(4, 4);
+        input.statement = GuestStatement::Batch(vec![]);
+        assert_eq!(input.validate(), Err(InputError::EmptyBatch));
+    }
+
+    #[test]
+    fn validate_checks_quotient_and_batch_statements() {
+        let input = guest_input(4, 4);
+        let GuestStatement::Pairing(opening) = input.statement.clone() else { unreachable!() };
+
+        let quotient = |poly_len: usize| SerializableQuotientOpening::<Bls12_381> {
+            commitment: opening.commitment,
+            points: opening.points.clone(),
+            values: opening.values.clone(),
+            poly: vec![Fr::from(1u64); poly_len],
+            quotient: vec![],
+        };
+        let mut input = guest_input(4, 4);
+        input.statement = GuestStatement::QuotientCheck(quotient(5));
+        assert_eq!(input.validate(), Ok(()));
+        input.statement = GuestStatement::QuotientCheck(quotient(6));
+        assert_eq!(input.validate(), Err(InputError::DegreeTooLarge));
+
+        // one bad opening fails the whole batch
+        let mut too_many = opening.clone();
+        too_many.points.push(Fr::from(1u64));
+        too_many.values.push(Fr::from(2u64));
+        input.statement = GuestStatement::Batch(vec![opening.clone(), opening.clone()]);
+        assert_eq!(input.validate(), Ok(()));
+        input.statement = GuestStatement::Batch(vec![opening, too_many]);
+        assert_eq!(input.validate(), Err(InputError::TooManyPoints));
+    }
+
+    // a point on the curve outside the prime order subgroup, which holds almost every point
+    // since the cofactor is large
+    fn point_outside_subgroup() -> G1Projective {
+        (1u64..)
+            .filter_map(|x| G1Affine::get_point_from_x_unchecked(Fq::from(x), false))
+            .find(|point| !point.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap()
+            .into()
+    }
+
+    // the uncompressed encoding of a random point with the last byte of y changed,
+    // which leaves it off the curve
+    fn point_off_curve() -> Vec<u8> {
+        let mut bytes = Ark(G1Projective::generator() * Fr::from(7u64)).to_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        bytes
+    }
+
+    #[test]
+    fn decode_rejects_points_off_the_curve() {
+        let bytes = point_off_curve();
+        assert_eq!(Ark::<G1Projective>::decode(&bytes, ValidationMode::Full), Err(DecodeError::NotOnCurve));
+        assert_eq!(Ark::<G1Projective>::try_from(&bytes[..]), Err(DecodeError::NotOnCurve));
+        assert!(Ark::<G1Projective>::decode(&bytes, ValidationMode::Trusted).is_ok());
+    }
+
+    #[test]
+    fn decode_rejects_points_outside_the_subgroup() {
+        let point = point_outside_subgroup();
+        assert_eq!(point.curve_check(), Err(DecodeError::NotInSubgroup));
+        assert_eq!(Ark::<G1Projective>::try_from(&Ark(point).to_bytes()[..]), Err(DecodeError::NotInSubgroup));
+        assert_eq!(
+            ArkCompressed::<G1Projective>::try_from(&ArkCompressed(point).to_bytes()[..]),
+            Err(DecodeError::NotInSubgroup)
+        );
+        let trusted = Ark::<G1Projective>::decode(&Ark(point).to_bytes(), ValidationMode::Trusted).unwrap();
+        assert_eq!(trusted.0, point);
+    }
+
+    #[test]
+    fn decode_rejects_trailing_bytes() {
+        let mut bytes = ArkCompressed(G1Projective::generator()).to_bytes();
+        bytes.push(0);
+        assert_eq!(ArkCompressed::<G1Projective>::try_from(&bytes[..]), Err(DecodeError::TrailingBytes));
+        assert_eq!(
+            ArkCompressed::<G1Projective>::decode(&bytes, ValidationMode::Trusted),
+            Err(DecodeError::TrailingBytes)
+        );
+        assert_eq!(ArkCompressed::<G1Projective>::try_from(&bytes[..47]), Err(DecodeError::InvalidEncoding));
+    }
+
+    #[test]
+    fn decode_rejects_non_canonical_scalars_in_both_modes() {
+        let modulus = Fr::MODULUS.to_bytes_le();
+        assert_eq!(modulus.len(), 32);
+        for validation in [ValidationMode::Full, ValidationMode::Trusted] {
+            assert_eq!(Ark::<Fr>::decode(&modulus, validation), Err(DecodeError::InvalidEncoding));
+        }
+
+        let mut largest = modulus.clone();
+        largest[0] -= 1;
+        assert_eq!(Ark::<Fr>::decode(&largest, ValidationMode::Trusted).unwrap().0, -Fr::from(1u64));
+    }
+
+    #[test]
+    fn from_hex_rejects_malformed_strings() {
+        assert_eq!(from_hex("0x00ff"), Ok(vec![0x00, 0xff]));
+        assert_eq!(from_hex("0xABcd"), Ok(vec![0xab, 0xcd]));
+        assert_eq!(from_hex("0x"), Ok(vec![]));
+        assert_eq!(from_hex("00ff"), Err(DecodeError::InvalidHex));
+        assert_eq!(from_hex("0x0ff"), Err(DecodeError::InvalidHex));
+        assert_eq!(from_hex("0x0g"), Err(DecodeError::InvalidHex));
+        assert_eq!(from_hex("0x+f"), Err(DecodeError::InvalidHex));
+    }
+
+    // a JSON round trip, where every value is a hex string, and a bincode round trip of raw bytes
+    fn round_trip<T: Serialize + serde::de::DeserializeOwned + PartialEq + fmt::Debug>(value: &T) -> String {
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(serde_json::from_str::<T>(&json).unwrap(), *value);
+        let bytes = bincode::serialize(value).unwrap();
+        assert_eq!(bincode::deserialize::<T>(&bytes).unwrap(), *value);
+        json
+    }
+
+    #[test]
+    fn files_round_trip() {
+        let g1 = G1Projective::generator();
+        let g2 = G2Projective::generator();
+
+        let proof = ProofFile::<Bls12_381> {
+            commitment: ArkCompressed(g1 * Fr::from(3u64)),
+            point: Ark(Fr::from(5u64)),
+            value: Ark(Fr::from(7u64)),
+            proof: ArkCompressed(g1),
+        };
+        let json = round_trip(&proof);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["proof"], G1_GENERATOR);
+        assert_eq!(parsed["point"].as_str().unwrap().len(), 2 + 64);
+
+        round_trip(&MultiProofFile::<Bls12_381> {
+            commitment: ArkCompressed(g1),
+            points: vec![Ark(Fr::from(1u64)), Ark(Fr::from(2u64))],
+            values: vec![Ark(Fr::from(3u64)), Ark(Fr::from(4u64))],
+            proof: ArkCompressed(g1 * Fr::from(2u64)),
+        });
+
+        let json = round_trip(&SrsFile::<Bls12_381> {
+            g1: ArkCompressed(g1),
+            g2: ArkCompressed(g2),
+            g2_tau: ArkCompressed(g2 * Fr::from(9u64)),
+            crs_g1: vec![ArkCompressed(g1), ArkCompressed(g1 * Fr::from(9u64))],
+            crs_g2: vec![ArkCompressed(g2), ArkCompressed(g2 * Fr::from(9u64))],
+        });
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["g2"], G2_GENERATOR);
+    }
+
+    #[test]
+    fn json_decoding_checks_the_points() {
+        let json = |commitment: &str| {
+            let zero = format!("0x{}", "00".repeat(32));
+            format!(
+                r#"{{"commitment":"{}","point":"{}","value":"{}","proof":"{}"}}"#,
+                commitment, zero, zero, G1_GENERATOR
+            )
+        };
+        assert!(serde_json::from_str::<ProofFile<Bls12_381>>(&json(G1_GENERATOR)).is_ok());
+        let outside = to_hex(&ArkCompressed(point_outside_subgroup()).to_bytes());
+        assert!(serde_json::from_str::<ProofFile<Bls12_381>>(&json(&outside)).is_err());
+        assert!(serde_json::from_str::<ProofFile<Bls12_381>>(&json(&G1_GENERATOR[2..])).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Opens a KZG commitment over BLS12-377 and recomputes the G1 side of its check with
+//! BW6-761 scalars.
+//!
+//! BLS12-377 points have their coordinates in the BLS12-377 base field, which is the
+//! scalar field of BW6-761. A BW6-761 proof system therefore handles them natively and
+//! can verify BLS12-377 openings, and proofs, in a recursive step. Here only the G1
+//! linear combination of the check runs on BW6-761 scalars, as the constraints of such a
+//! circuit would; the pairing itself is still the native BLS12-377 one, a circuit would
+//! need a pairing gadget for it. The statement is then committed to with a BW6-761 KZG,
+//! as an outer proof would see it.
+//!
+//!     cargo run --release --features recursion --example bls12_377
+
+use ark_bls12_377::{Bls12_377, Fr, G1Affine, G1Projective as G1, G2Projective as G2};
+use ark_bw6_761::BW6_761;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_serialize::Compress;
+use ark_std::UniformRand;
+use lib::ValidationMode;
+use multiproof::kzg::KZG;
+use multiproof::polynomial::Polynomial;
+
+// the scalar field of BW6-761, the same type as the BLS12-377 base field
+type Native = <BW6_761 as Pairing>::ScalarField;
+
+// an affine BLS12-377 G1 point in BW6-761 scalars, None is the point at infinity
+type NativePoint = Option<(Native, Native)>;
+
+fn to_native(point: &G1) -> NativePoint {
+    point.into_affine().xy().map(|(x, y)| (*x, *y))
+}
+
+fn from_native(point: NativePoint) -> G1 {
+    match point {
+        Some((x, y)) => G1Affine::new(x, y).into(),
+        None => G1::zero(),
+    }
+}
+
+// affine addition and doubling on y^2 = x^3 + 1
+fn add(p: NativePoint, q: NativePoint) -> NativePoint {
+    let ((x1, y1), (x2, y2)) = match (p, q) {
+        (None, q) => return q,
+        (p, None) => return p,
+        (Some(p), Some(q)) => (p, q),
+    };
+    let lambda = if x1 == x2 {
+        if y1 != y2 || y1.is_zero() {
+            return None;
+        }
+        x1.square() * Native::from(3u64) * y1.double().inverse().unwrap()
+    } else {
+        (y2 - y1) * (x2 - x1).inverse().unwrap()
+    };
+    let x3 = lambda.square() - x1 - x2;
+    let y3 = lambda * (x1 - x3) - y1;
+    Some((x3, y3))
+}
+
+fn neg(p: NativePoint) -> NativePoint {
+    p.map(|(x, y)| (x, -y))
+}
+
+// double and add over the bits of a BLS12-377 scalar, most significant first
+fn mul(p: NativePoint, scalar: &Fr) -> NativePoint {
+    let mut result = None;
+    for bit in scalar.into_bigint().to_bits_be() {
+        result = add(result, result);
+        if bit {
+            result = add(result, p);
+        }
+    }
+    result
+}
+
+fn main() {
+    let mut rng = ark_std::test_rng();
+    let degree = 16;
+
+    // trusted setup, stored and loaded back the way a setup file would be
+    let mut setup = KZG::<Bls12_377>::new(G1::rand(&mut rng), G2::rand(&mut rng), degree);
+    setup.setup(Fr::rand(&mut rng));
+    let mut srs = Vec::new();
+    setup.write_srs(&mut srs, Compress::Yes).expect("writing the SRS failed");
+    let kzg = KZG::<Bls12_377>::read_srs(&srs[..], ValidationMode::Full).expect("loading the SRS failed");
+    println!("loaded a BLS12-377 SRS of degree {} ({} bytes)", kzg.degree, srs.len());
+
+    // commit to a random polynomial and open it at a random point
+    let poly = Polynomial::new((0..degree + 1).map(|_| Fr::rand(&mut rng)).collect());
+    let commitment = kzg.commit(&poly).unwrap();
+    let proof = kzg.open(&poly, Fr::rand(&mut rng)).unwrap();
+    assert!(proof.verify(&kzg.vk, &commitment), "opening was rejected");
+    println!("opening verified over BLS12-377");
+
+    // the G1 side of the check, commitment - value * g1 + point * pi, in BW6-761 scalars
+    let lhs = add(
+        add(to_native(&commitment), neg(mul(to_native(&kzg.g1), &proof.value))),
+        mul(to_native(&proof.pi), &proof.point),
+    );
+    assert_eq!(from_native(lhs), commitment - kzg.g1 * proof.value + proof.pi * proof.point);
+
+    // e(pi, g2_tau) * e(-lhs, g2) == 1 with the native BLS12-377 pairing, a BW6-761
+    // circuit would check it with a pairing gadget instead
+    let pairing = Bls12_377::multi_pairing([proof.pi, -from_native(lhs)], [kzg.g2_tau, kzg.g2]);
+    assert!(pairing.is_zero(), "opening was rejected");
+    println!("G1 combination recomputed with BW6-761 scalars, pairing checked natively over BLS12-377");
+
+    // the coordinates of the statement are BW6-761 scalars, so an outer KZG over
+    // BW6-761 commits to them directly without any non-native encoding
+    let coordinates: Vec<Native> = [commitment, proof.pi]
+        .iter()
+        .flat_map(|point| {
+            let (x, y) = to_native(point).expect("point at infinity");
+            [x, y]
+        })
+        .collect();
+    let mut outer = KZG::<BW6_761>::new(
+        <BW6_761 as Pairing>::G1::rand(&mut rng),
+        <BW6_761 as Pairing>::G2::rand(&mut rng),
+        coordinates.len() - 1,
+    );
+    outer.setup(Native::rand(&mut rng));
+    let statement = Polynomial::new(coordinates);
+    let outer_commitment = outer.commit(&statement).unwrap();
+    let outer_proof = outer.open(&statement, Native::rand(&mut rng)).unwrap();
+    assert!(outer_proof.verify(&outer.vk, &outer_commitment), "outer opening was rejected");
+    println!("statement committed and opened over BW6-761");
+}
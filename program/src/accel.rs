@@ -0,0 +1,109 @@
+use ark_bls12_381::{Fr, G1Projective, G2Projective};
+use ark_ec::CurveGroup;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use bls12_381::{multi_miller_loop, G1Affine, G2Affine, G2Prepared, Gt, Scalar};
+
+// arkworks and the bls12_381 crate both encode points in the ZCash format
+// (big-endian coordinates, flags in the top bits of the first byte), so points
+// cross over through their uncompressed encoding without any validation work
+
+fn to_g1(point: &G1Projective) -> G1Affine {
+    let mut bytes = [0u8; 96];
+    point
+        .into_affine()
+        .serialize_uncompressed(&mut bytes[..])
+        .expect("G1 point does not fit in 96 bytes");
+    G1Affine::from_uncompressed_unchecked(&bytes).unwrap()
+}
+
+fn from_g1(point: &G1Affine) -> G1Projective {
+    ark_bls12_381::G1Affine::deserialize_uncompressed_unchecked(&point.to_uncompressed()[..])
+        .expect("Invalid G1 encoding")
+        .into()
+}
+
+fn to_g2(point: &G2Projective) -> G2Affine {
+    let mut bytes = [0u8; 192];
+    point
+        .into_affine()
+        .serialize_uncompressed(&mut bytes[..])
+        .expect("G2 point does not fit in 192 bytes");
+    G2Affine::from_uncompressed_unchecked(&bytes).unwrap()
+}
+
+// both crates store scalars as 32 little-endian bytes
+fn to_scalar(scalar: &Fr) -> Scalar {
+    let mut bytes = [0u8; 32];
+    scalar
+        .serialize_compressed(&mut bytes[..])
+        .expect("Scalar does not fit in 32 bytes");
+    Scalar::from_bytes(&bytes).unwrap()
+}
+
+pub fn prepare_bases(bases: &[G1Projective]) -> Vec<G1Affine> {
+    bases.iter().map(to_g1).collect()
+}
+
+pub fn msm(bases: &[G1Projective], scalars: &[Fr]) -> G1Projective {
+    let bases: Vec<G1Affine> = bases.iter().take(scalars.len()).map(to_g1).collect();
+    msm_prepared(&bases, scalars)
+}
+
+pub fn msm_prepared(bases: &[G1Affine], scalars: &[Fr]) -> G1Projective {
+    let mut result = bls12_381::G1Projective::identity();
+    for (base, scalar) in bases.iter().zip(scalars.iter()) {
+        result += base * to_scalar(scalar);
+    }
+    from_g1(&G1Affine::from(result))
+}
+
+pub fn prepare_g2(point: &G2Projective) -> G2Prepared {
+    G2Prepared::from(to_g2(point))
+}
+
+pub fn pairing_check(g1: &[G1Projective], g2: &[G2Prepared]) -> bool {
+    let g1: Vec<G1Affine> = g1.iter().map(to_g1).collect();
+    let terms: Vec<(&G1Affine, &G2Prepared)> = g1.iter().zip(g2.iter()).collect();
+    multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{arkworks_msm, arkworks_pairing_check};
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::pairing::Pairing;
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn msm_matches_arkworks() {
+        let rng = &mut test_rng();
+        let bases: Vec<G1Projective> = (0..17).map(|_| G1Projective::rand(rng)).collect();
+        // fewer scalars than bases, the scalars bound the sum
+        let scalars: Vec<Fr> = (0..13).map(|_| Fr::rand(rng)).collect();
+        let expected = arkworks_msm::<Bls12_381>(&bases, &scalars);
+        assert_eq!(msm(&bases, &scalars), expected);
+        assert_eq!(msm_prepared(&prepare_bases(&bases), &scalars), expected);
+    }
+
+    #[test]
+    fn pairing_check_matches_arkworks() {
+        let rng = &mut test_rng();
+        let a = Fr::rand(rng);
+        let b = Fr::rand(rng);
+        let g1 = G1Projective::rand(rng);
+        let g2 = G2Projective::rand(rng);
+        let g2_b = g2 * b;
+        let accel_g2 = [prepare_g2(&g2), prepare_g2(&g2_b)];
+        let ark_g2: Vec<<Bls12_381 as Pairing>::G2Prepared> = vec![g2.into(), g2_b.into()];
+
+        // e(a*b*g1, g2) * e(-a*g1, b*g2) == 1
+        let valid = [g1 * (a * b), -(g1 * a)];
+        assert!(arkworks_pairing_check::<Bls12_381>(&valid, &ark_g2));
+        assert!(pairing_check(&valid, &accel_g2));
+
+        let invalid = [g1 * (a * b + Fr::from(1u64)), -(g1 * a)];
+        assert!(!arkworks_pairing_check::<Bls12_381>(&invalid, &ark_g2));
+        assert!(!pairing_check(&invalid, &accel_g2));
+    }
+}
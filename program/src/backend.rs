@@ -0,0 +1,120 @@
+use std::fmt::Debug;
+use ark_ec::pairing::Pairing;
+use ark_std::Zero;
+use ark_bls12_381::Bls12_381;
+#[cfg(feature = "bn254")]
+use ark_bn254::Bn254;
+#[cfg(feature = "recursion")]
+use ark_bls12_377::Bls12_377;
+#[cfg(feature = "recursion")]
+use ark_bw6_761::BW6_761;
+
+// the curve operations dominating the verifier cost: commitments by MSM in G1 and
+// products of pairings checked against the identity. a curve either runs them with
+// the plain arkworks arithmetic below or routes them to accelerated code
+pub trait PairingBackend: Pairing {
+    // G2 element with whatever precomputation the backend does ahead of a pairing
+    type PreparedG2: Clone + Debug;
+
+    fn prepare_g2(point: Self::G2) -> Self::PreparedG2;
+
+    // G1 bases in whatever form the backend runs its MSMs on, converted once for bases
+    // used again and again such as crs_g1
+    type PreparedBases: Clone + Debug;
+
+    fn prepare_bases(bases: &[Self::G1]) -> Self::PreparedBases;
+
+    // sum_i bases[i] * scalars[i], the shorter of the two slices bounds the sum
+    fn msm(bases: &[Self::G1], scalars: &[Self::ScalarField]) -> Self::G1;
+
+    // msm on bases from prepare_bases
+    fn msm_prepared(bases: &Self::PreparedBases, scalars: &[Self::ScalarField]) -> Self::G1;
+
+    // check prod_i e(g1[i], g2[i]) == 1
+    fn pairing_check(g1: &[Self::G1], g2: &[Self::PreparedG2]) -> bool;
+}
+
+// pure arkworks implementations, always available so an accelerated backend can be cross-checked
+pub fn arkworks_msm<E: Pairing>(bases: &[E::G1], scalars: &[E::ScalarField]) -> E::G1 {
+    let mut result = E::G1::zero();
+    for (base, scalar) in bases.iter().zip(scalars.iter()) {
+        result += *base * scalar;
+    }
+    result
+}
+
+pub fn arkworks_pairing_check<E: Pairing>(g1: &[E::G1], g2: &[E::G2Prepared]) -> bool {
+    E::multi_pairing(g1.iter().copied(), g2.iter().cloned()).is_zero()
+}
+
+// a backend running everything on the arkworks arithmetic above
+macro_rules! impl_arkworks_backend {
+    ($($curve:ty),*) => {
+        $(
+            impl PairingBackend for $curve {
+                type PreparedG2 = <Self as Pairing>::G2Prepared;
+
+                fn prepare_g2(point: Self::G2) -> Self::PreparedG2 {
+                    point.into()
+                }
+
+                type PreparedBases = Vec<Self::G1>;
+
+                fn prepare_bases(bases: &[Self::G1]) -> Self::PreparedBases {
+                    bases.to_vec()
+                }
+
+                fn msm(bases: &[Self::G1], scalars: &[Self::ScalarField]) -> Self::G1 {
+                    arkworks_msm::<Self>(bases, scalars)
+                }
+
+                fn msm_prepared(bases: &Self::PreparedBases, scalars: &[Self::ScalarField]) -> Self::G1 {
+                    arkworks_msm::<Self>(bases, scalars)
+                }
+
+                fn pairing_check(g1: &[Self::G1], g2: &[Self::PreparedG2]) -> bool {
+                    arkworks_pairing_check::<Self>(g1, g2)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(not(feature = "precompiles"))]
+impl_arkworks_backend!(Bls12_381);
+
+// with the `precompiles` feature the BLS12-381 arithmetic goes through the SP1
+// patched bls12_381 crate, whose base field operations are zkVM syscalls
+#[cfg(feature = "precompiles")]
+impl PairingBackend for Bls12_381 {
+    type PreparedG2 = bls12_381::G2Prepared;
+
+    fn prepare_g2(point: Self::G2) -> Self::PreparedG2 {
+        crate::accel::prepare_g2(&point)
+    }
+
+    type PreparedBases = Vec<bls12_381::G1Affine>;
+
+    fn prepare_bases(bases: &[Self::G1]) -> Self::PreparedBases {
+        crate::accel::prepare_bases(bases)
+    }
+
+    fn msm(bases: &[Self::G1], scalars: &[Self::ScalarField]) -> Self::G1 {
+        crate::accel::msm(bases, scalars)
+    }
+
+    fn msm_prepared(bases: &Self::PreparedBases, scalars: &[Self::ScalarField]) -> Self::G1 {
+        crate::accel::msm_prepared(bases, scalars)
+    }
+
+    fn pairing_check(g1: &[Self::G1], g2: &[Self::PreparedG2]) -> bool {
+        crate::accel::pairing_check(g1, g2)
+    }
+}
+
+// the other curves always run on arkworks, the precompiles feature only covers BLS12-381
+#[cfg(feature = "bn254")]
+impl_arkworks_backend!(Bn254);
+
+#[cfg(feature = "recursion")]
+impl_arkworks_backend!(Bls12_377, BW6_761);
@@ -0,0 +1,185 @@
+use ark_ff::{batch_inversion, FftField};
+use crate::backend::PairingBackend;
+use crate::fft::Domain;
+use crate::polynomial::Polynomial;
+
+// polynomial in evaluation form, given by its values over the elements
+// of a radix-2 domain in natural order (evals[i] = p(omega^i))
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Evaluations<F: FftField> {
+    evals: Vec<F>,
+    domain: Domain<F>,
+}
+
+impl<F: FftField> Evaluations<F> {
+    pub fn from_vec_and_domain(evals: Vec<F>, domain: Domain<F>) -> Result<Self, &'static str> {
+        if evals.len() != domain.size {
+            return Err("Number of evaluations does not match the domain size");
+        }
+        Ok(Self { evals, domain })
+    }
+
+    // evaluations over the domain of size evals.len(), which must be a power of two
+    pub fn new(evals: Vec<F>) -> Result<Self, &'static str> {
+        let domain = Domain::new(evals.len()).ok_or("Domain is too large for the field")?;
+        Self::from_vec_and_domain(evals, domain)
+    }
+
+    pub fn from_polynomial(poly: &Polynomial<F>, domain: Domain<F>) -> Result<Self, &'static str> {
+        if poly.coeffs().len() > domain.size {
+            return Err("Polynomial degree exceeds the domain size");
+        }
+        Ok(Self {
+            evals: domain.fft(poly.coeffs()),
+            domain,
+        })
+    }
+
+    pub fn evals(&self) -> &[F] {
+        &self.evals
+    }
+
+    pub fn domain(&self) -> &Domain<F> {
+        &self.domain
+    }
+
+    // convert back to coefficient form with an IFFT
+    pub fn interpolate(&self) -> Polynomial<F> {
+        Polynomial::new(self.domain.ifft(&self.evals))
+    }
+
+    // evaluate at an arbitrary point in O(n) with the barycentric formula
+    //   p(z) = (z^n - 1) / n * sum_i evals[i] * omega^i / (z - omega^i)
+    pub fn evaluate(&self, point: F) -> F {
+        let vanishing = self.domain.evaluate_vanishing(point);
+        if vanishing.is_zero() {
+            // the formula breaks down on the domain itself, where the value is known
+            return self.evals[self.index_of(point)];
+        }
+
+        let elements = self.domain.elements();
+        let mut denominators: Vec<F> = elements.iter().map(|&x| point - x).collect();
+        batch_inversion(&mut denominators);
+
+        let mut sum = F::ZERO;
+        for i in 0..self.evals.len() {
+            sum += self.evals[i] * elements[i] * denominators[i];
+        }
+        sum * vanishing * self.domain.size_inv
+    }
+
+    // evaluations of the opening quotient q(x) = (p(x) - p(z)) / (x - z) over the domain,
+    // returned together with the value p(z)
+    pub fn quotient(&self, point: F) -> (F, Self) {
+        let value = self.evaluate(point);
+        let elements = self.domain.elements();
+        let n = self.evals.len();
+
+        let index = if self.domain.evaluate_vanishing(point).is_zero() {
+            Some(self.index_of(point))
+        } else {
+            None
+        };
+
+        let mut denominators: Vec<F> = elements.iter().map(|&x| x - point).collect();
+        if let Some(m) = index {
+            // placeholder to keep batch_inversion away from the zero denominator
+            denominators[m] = F::ONE;
+        }
+        batch_inversion(&mut denominators);
+
+        let mut quotient: Vec<F> = (0..n)
+            .map(|i| (self.evals[i] - value) * denominators[i])
+            .collect();
+
+        if let Some(m) = index {
+            // on the opened point itself q(omega^m) = -sum_{i != m} q(omega^i) * omega^(i - m),
+            // which follows from p'(omega^m) and A'(x) = n / x for A(x) = x^n - 1
+            let mut sum = F::ZERO;
+            for i in 0..n {
+                if i != m {
+                    sum += quotient[i] * elements[(i + n - m) % n];
+                }
+            }
+            quotient[m] = -sum;
+        }
+
+        (value, Self { evals: quotient, domain: self.domain })
+    }
+
+    // commit in the Lagrange basis: sum_i evals[i] * L_i(tau), given the
+    // basis [L_0(tau)], ..., [L_{n-1}(tau)] in natural order
+    pub fn commit<E: PairingBackend<ScalarField = F>>(&self, lagrange_basis: &[E::G1]) -> Result<E::G1, &'static str> {
+        if lagrange_basis.len() != self.evals.len() {
+            return Err("Lagrange basis does not match the domain size");
+        }
+
+        Ok(E::msm(lagrange_basis, &self.evals))
+    }
+
+    // position of a domain element
+    fn index_of(&self, point: F) -> usize {
+        self.domain
+            .elements()
+            .iter()
+            .position(|&x| x == point)
+            .expect("Point is not in the domain")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective as G1};
+    use ark_std::UniformRand;
+
+    fn random_poly(len: usize) -> Polynomial<Fr> {
+        let mut rng = ark_std::test_rng();
+        Polynomial::new((0..len).map(|_| Fr::rand(&mut rng)).collect())
+    }
+
+    #[test]
+    fn evaluate_matches_horner() {
+        let domain = Domain::new(16).unwrap();
+        let poly = random_poly(13);
+        let evals = Evaluations::from_polynomial(&poly, domain).unwrap();
+
+        let mut rng = ark_std::test_rng();
+        for point in [Fr::rand(&mut rng), Fr::rand(&mut rng), domain.element(7)] {
+            assert_eq!(evals.evaluate(point), poly.evaluate(point));
+        }
+    }
+
+    #[test]
+    fn interpolate_round_trip() {
+        let domain = Domain::new(16).unwrap();
+        let poly = random_poly(16);
+        let evals = Evaluations::from_polynomial(&poly, domain).unwrap();
+        assert_eq!(evals.interpolate(), poly);
+        assert_eq!(Evaluations::new(evals.evals().to_vec()).unwrap(), evals);
+    }
+
+    #[test]
+    fn evaluation_count_must_match_the_domain() {
+        let domain = Domain::<Fr>::new(8).unwrap();
+        assert_eq!(
+            Evaluations::from_vec_and_domain(vec![Fr::from(1u64); 7], domain).unwrap_err(),
+            "Number of evaluations does not match the domain size"
+        );
+        assert_eq!(
+            Evaluations::from_polynomial(&random_poly(9), domain).unwrap_err(),
+            "Polynomial degree exceeds the domain size"
+        );
+    }
+
+    #[test]
+    fn commit_matches_the_lagrange_combination() {
+        let mut rng = ark_std::test_rng();
+        let basis: Vec<G1> = (0..8).map(|_| G1::rand(&mut rng)).collect();
+        let evals = Evaluations::new((0..8).map(|_| Fr::rand(&mut rng)).collect()).unwrap();
+
+        let expected = basis.iter().zip(evals.evals()).map(|(base, eval)| *base * eval).sum::<G1>();
+        assert_eq!(evals.commit::<Bls12_381>(&basis).unwrap(), expected);
+        assert!(evals.commit::<Bls12_381>(&basis[..7]).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::ops::{AddAssign, Mul, Sub};
+use ark_ff::FftField;
+use ark_std::Zero;
+
+// values the FFT can run over: field elements themselves, or group
+// elements such as an SRS, which are scaled by field elements
+pub trait FftCoeff<F: FftField>:
+    Copy + Zero + AddAssign + Sub<Output = Self> + Mul<F, Output = Self>
+{
+}
+
+impl<F: FftField, T> FftCoeff<F> for T where
+    T: Copy + Zero + AddAssign + Sub<Output = T> + Mul<F, Output = T>
+{
+}
+
+// radix-2 evaluation domain made of the `size`-th roots of unity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Domain<F: FftField> {
+    pub size: usize,
+    pub log_size: u32,
+    pub omega: F,
+    pub omega_inv: F,
+    pub size_inv: F,
+}
+
+impl<F: FftField> Domain<F> {
+    // returns the smallest domain holding at least `size` elements,
+    // or None if the field does not have a large enough root of unity
+    pub fn new(size: usize) -> Option<Self> {
+        let size = size.max(1).checked_next_power_of_two()?;
+        let log_size = size.trailing_zeros();
+        if log_size > F::TWO_ADICITY {
+            return None;
+        }
+
+        let omega = F::get_root_of_unity(size as u64)?;
+        Some(Self {
+            size,
+            log_size,
+            omega,
+            omega_inv: omega.inverse()?,
+            size_inv: F::from(size as u64).inverse()?,
+        })
+    }
+
+    // i-th element of the domain, omega^i
+    pub fn element(&self, i: usize) -> F {
+        self.omega.pow([i as u64])
+    }
+
+    // all elements of the domain in natural order
+    pub fn elements(&self) -> Vec<F> {
+        let mut elements = Vec::with_capacity(self.size);
+        let mut current = F::ONE;
+        for _ in 0..self.size {
+            elements.push(current);
+            current *= self.omega;
+        }
+        elements
+    }
+
+    // evaluate the vanishing polynomial of the domain, x^n - 1, at point
+    pub fn evaluate_vanishing(&self, point: F) -> F {
+        point.pow([self.size as u64]) - F::ONE
+    }
+
+    // evaluate the polynomial given by its coefficients over the domain
+    pub fn fft<T: FftCoeff<F>>(&self, coeffs: &[T]) -> Vec<T> {
+        assert!(coeffs.len() <= self.size, "Polynomial does not fit in the domain");
+        let mut values = coeffs.to_vec();
+        values.resize(self.size, T::zero());
+        fft_in_place(&mut values, self.omega);
+        values
+    }
+
+    // interpolate the coefficients of a polynomial from its evaluations over the domain
+    pub fn ifft<T: FftCoeff<F>>(&self, evals: &[T]) -> Vec<T> {
+        assert!(evals.len() <= self.size, "Too many evaluations for the domain");
+        let mut coeffs = evals.to_vec();
+        coeffs.resize(self.size, T::zero());
+        fft_in_place(&mut coeffs, self.omega_inv);
+        for coeff in coeffs.iter_mut() {
+            *coeff = *coeff * self.size_inv;
+        }
+        coeffs
+    }
+}
+
+// reorder values so that index i holds the element previously at the
+// bit-reversal of i, `values.len()` must be a power of two
+pub fn bit_reverse_permutation<T>(values: &mut [T]) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+// iterative Cooley-Tukey butterfly, `values.len()` must be a power of two
+// and `omega` a primitive root of unity of that order
+fn fft_in_place<F: FftField, T: FftCoeff<F>>(values: &mut [T], omega: F) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+
+    // reorder the input so the butterflies can work in place
+    bit_reverse_permutation(values);
+
+    let mut half = 1;
+    while half < n {
+        let step = omega.pow([(n / (2 * half)) as u64]);
+        for start in (0..n).step_by(2 * half) {
+            let mut w = F::ONE;
+            for j in start..start + half {
+                let t = values[j + half] * w;
+                values[j + half] = values[j] - t;
+                values[j] += t;
+                w *= step;
+            }
+        }
+        half *= 2;
+    }
+}
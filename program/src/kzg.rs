@@ -1,26 +1,402 @@
 use std::ops::Mul;
 use ark_ff::Field;
 use ark_ec::pairing::Pairing;
-use crate::utils::{div, mul, evaluate, interpolate};
+use std::io::{Read, Write};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress};
+use lib::container::{read_container, write_container, ContainerError, KnownCurve, ObjectKind};
+use lib::{crs_digest, setup_digest, CurveCheck, DecodeError, SerializableMultiOpening, SerializableSrs, SerializableVerifierKey, ValidationMode, EMPTY_CRS_DIGEST};
+use crate::backend::PairingBackend;
+use crate::evaluations::Evaluations;
+use crate::fft::{bit_reverse_permutation, Domain};
+use crate::lagrange::{evaluate_interpolation, LagrangeInterpContext};
+use crate::polynomial::Polynomial;
+use crate::transcript::Transcript;
 
-pub struct KZG<E: Pairing> {
+// returned when the numerator of an opening is not divisible by its denominator,
+// i.e. the values being opened do not match the polynomial at the given points
+const NON_ZERO_REMAINDER: &str = "Opening remainder is not zero";
+
+// domain separator of the transcript the quotient check draws its challenge from
+const QUOTIENT_CHECK_LABEL: &[u8] = b"kzg-multi-open-quotient-check";
+
+// domain separator of the transcript the batch check draws its challenge from
+const BATCH_CHECK_LABEL: &[u8] = b"kzg-multi-open-batch-check";
+
+pub struct KZG<E: PairingBackend> {
     pub g1: E::G1,
     pub g2: E::G2,
     pub g2_tau: E::G2,
     pub degree: usize,
     pub crs_g1: Vec<E::G1>,
     pub crs_g2: Vec<E::G2>,
+    pub crs_lagrange: Vec<LagrangeCrs<E>>,
+    pub vk: VerifierKey<E>,
+}
+
+// the part of the setup a verifier needs: the generators, g2_tau and a prefix of
+// crs_g1 long enough to commit to the zero and Lagrange polynomials of the largest
+// point set it checks, with the Miller loop precomputation of the G2 elements and the
+// backend conversion of crs_g1 done once
+#[derive(Clone, Debug)]
+pub struct VerifierKey<E: PairingBackend> {
+    pub g1: E::G1,
+    pub g2: E::G2,
+    pub g2_tau: E::G2,
+    pub crs_g1: Vec<E::G1>,
+    pub crs_g1_prepared: E::PreparedBases,
+    pub g2_prepared: E::PreparedG2,
+    pub g2_tau_prepared: E::PreparedG2,
+}
+
+impl<E: PairingBackend> VerifierKey<E> {
+    pub fn new(g1: E::G1, g2: E::G2, g2_tau: E::G2, crs_g1: Vec<E::G1>) -> Self {
+        Self {
+            g1,
+            g2,
+            g2_tau,
+            crs_g1_prepared: E::prepare_bases(&crs_g1),
+            crs_g1,
+            g2_prepared: E::prepare_g2(g2),
+            g2_tau_prepared: E::prepare_g2(g2_tau),
+        }
+    }
+
+    // largest number of points a multi-proof checked with this key can open
+    pub fn max_points(&self) -> usize {
+        self.crs_g1.len().saturating_sub(1)
+    }
+
+    pub fn verify(
+        &self,
+        point: E::ScalarField,
+        value: E::ScalarField,
+        commitment: E::G1,
+        pi: E::G1
+    ) -> bool {
+        // e(pi, g2_tau - point * g2) == e(commitment - value * g1, g2) is rearranged to
+        // e(pi, g2_tau) * e(-(commitment - value * g1 + point * pi), g2) == 1 so that both
+        // G2 inputs are fixed and only one final exponentiation is needed
+        let lhs = commitment - self.g1.mul(value) + pi.mul(point);
+        E::pairing_check(
+            &[pi, -lhs],
+            &[self.g2_tau_prepared.clone(), self.g2_prepared.clone()]
+        )
+    }
+
+    // verify a multi-proof taking the zero polynomial commitment in G2 and the Lagrange
+    // polynomial as untrusted hints, so the G2 MSM can be done outside of the verifier.
+    // the Lagrange polynomial is checked by evaluating it at the points, and the
+    // zero commitment against its G1 counterpart with e(Z_g1, g2) == e(g1, Z_g2)
+    pub fn verify_multi_with_hints(
+        &self,
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        commitment: E::G1,
+        pi: E::G1,
+        zero_commitment: E::G2,
+        lagrange_poly: &Polynomial<E::ScalarField>
+    ) -> bool {
+        // a batch of one: the zero commitment check and the opening check are folded with
+        // a transcript challenge into one product of pairings, i.e. one final exponentiation
+        self.batch_verify_multi_with_hints(&[MultiOpening {
+            commitment,
+            points: points.to_vec(),
+            values: values.to_vec(),
+            pi,
+            zero_commitment,
+            lagrange_poly: lagrange_poly.clone(),
+        }])
+    }
+
+    // verify a multi-opening without pairings, taking the polynomial itself and the quotient
+    // q = (p - I) / Z as hints. the commitment is recomputed from the polynomial by MSM and
+    // p(x) - I(x) == q(x) * Z(x) is only checked at a challenge r drawn after both are fixed,
+    // by Schwartz-Zippel a false identity survives with probability at most deg / |F|.
+    // crs_g1 must cover the degree of the polynomial rather than the number of points
+    pub fn verify_multi_with_quotient(
+        &self,
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        commitment: E::G1,
+        poly: &Polynomial<E::ScalarField>,
+        quotient: &Polynomial<E::ScalarField>
+    ) -> bool {
+        if points.is_empty() || points.len() != values.len() || poly.coeffs().len() > self.crs_g1.len() {
+            return false;
+        }
+
+        // the hinted polynomial must be the committed one
+        if self.commit(poly) != commitment {
+            return false;
+        }
+
+        let mut transcript = Transcript::new(QUOTIENT_CHECK_LABEL);
+        transcript.append(b"commitment", &commitment);
+        transcript.append(b"points", points);
+        transcript.append(b"values", values);
+        transcript.append(b"poly", poly.coeffs());
+        transcript.append(b"quotient", quotient.coeffs());
+        let r: E::ScalarField = transcript.challenge(b"r");
+
+        // evaluate the zero and Lagrange polynomials at r without building them
+        let zero_at_r = points.iter().fold(E::ScalarField::ONE, |acc, &point| acc * (r - point));
+        let lagrange_at_r = match evaluate_interpolation(points, values, r) {
+            Ok(lagrange_at_r) => lagrange_at_r,
+            Err(_) => return false,
+        };
+
+        poly.evaluate(r) - lagrange_at_r == quotient.evaluate(r) * zero_at_r
+    }
+
+    // verify several multi-proofs at once, each with its own hints as in verify_multi_with_hints.
+    // the 2n pairing equations, e(Z_g1, g2) == e(g1, Z_g2) and e(pi, Z_g2) == e(C - I, g2) per
+    // opening, are combined with the powers of a transcript challenge gamma into
+    //   e(sum_i gamma^2i Z_g1_i + gamma^(2i+1) (I_i - C_i), g2) * prod_i e(gamma^(2i+1) pi_i - gamma^2i g1, Z_g2_i) == 1
+    // so that n + 1 Miller loops and a single final exponentiation check the whole batch
+    pub fn batch_verify_multi_with_hints(&self, openings: &[MultiOpening<E>]) -> bool {
+        if openings.is_empty() {
+            return false;
+        }
+
+        let mut transcript = Transcript::new(BATCH_CHECK_LABEL);
+        for opening in openings {
+            let points = &opening.points;
+            if points.is_empty() || points.len() != opening.values.len() || points.len() > self.max_points() {
+                return false;
+            }
+
+            // the hinted Lagrange polynomial must have degree < k and go through every (point, value)
+            if opening.lagrange_poly.coeffs().len() > points.len() {
+                return false;
+            }
+            for (&point, &value) in points.iter().zip(opening.values.iter()) {
+                if opening.lagrange_poly.evaluate(point) != value {
+                    return false;
+                }
+            }
+
+            transcript.append(b"commitment", &opening.commitment);
+            transcript.append(b"points", &opening.points[..]);
+            transcript.append(b"values", &opening.values[..]);
+            transcript.append(b"pi", &opening.pi);
+            transcript.append(b"zero_commitment", &opening.zero_commitment);
+        }
+        let gamma: E::ScalarField = transcript.challenge(b"gamma");
+
+        // fold the zero and Lagrange polynomials so their G1 commitments take a single MSM
+        let mut folded = Polynomial::zero();
+        let mut commitment_scalars = Vec::with_capacity(openings.len());
+        let mut g1_terms = Vec::with_capacity(openings.len() + 1);
+        let mut g2_terms = Vec::with_capacity(openings.len() + 1);
+        let mut power = E::ScalarField::ONE;
+        for opening in openings {
+            let (even, odd) = (power, power * gamma);
+            power = odd * gamma;
+
+            let zero_poly = Polynomial::from_roots(&opening.points);
+            folded = folded
+                + &zero_poly * &Polynomial::new(vec![even])
+                + &opening.lagrange_poly * &Polynomial::new(vec![odd]);
+            commitment_scalars.push(-odd);
+
+            g1_terms.push(opening.pi * odd - self.g1 * even);
+            g2_terms.push(E::prepare_g2(opening.zero_commitment));
+        }
+
+        let commitments: Vec<E::G1> = openings.iter().map(|opening| opening.commitment).collect();
+        g1_terms.push(self.commit(&folded) + E::msm(&commitments, &commitment_scalars));
+        g2_terms.push(self.g2_prepared.clone());
+
+        E::pairing_check(&g1_terms, &g2_terms)
+    }
+
+    // commit with the crs_g1 prefix, the caller checks the degree fits
+    fn commit(&self, poly: &Polynomial<E::ScalarField>) -> E::G1 {
+        E::msm_prepared(&self.crs_g1_prepared, poly.coeffs())
+    }
+}
+
+impl<E: PairingBackend> From<SerializableVerifierKey<E>> for VerifierKey<E> {
+    fn from(vk: SerializableVerifierKey<E>) -> Self {
+        VerifierKey::new(vk.g1, vk.g2, vk.g2_tau, vk.crs_g1)
+    }
+}
+
+impl<E: PairingBackend> From<&VerifierKey<E>> for SerializableVerifierKey<E> {
+    fn from(vk: &VerifierKey<E>) -> Self {
+        SerializableVerifierKey {
+            g1: vk.g1,
+            g2: vk.g2,
+            g2_tau: vk.g2_tau,
+            crs_g1: vk.crs_g1.clone(),
+            crs_g1_rest: EMPTY_CRS_DIGEST,
+        }
+    }
+}
+
+// a multi-opening with its proof pi and the zero commitment / Lagrange polynomial hints,
+// checked on its own by verify_multi_with_hints or in a batch by batch_verify_multi_with_hints
+#[derive(Clone, Debug)]
+pub struct MultiOpening<E: Pairing> {
+    pub commitment: E::G1,
+    pub points: Vec<E::ScalarField>,
+    pub values: Vec<E::ScalarField>,
+    pub pi: E::G1,
+    pub zero_commitment: E::G2,
+    pub lagrange_poly: Polynomial<E::ScalarField>,
+}
+
+impl<E: Pairing> From<SerializableMultiOpening<E>> for MultiOpening<E> {
+    fn from(opening: SerializableMultiOpening<E>) -> Self {
+        MultiOpening {
+            commitment: opening.commitment,
+            points: opening.points,
+            values: opening.values,
+            pi: opening.pi,
+            zero_commitment: opening.zero_commitment,
+            lagrange_poly: Polynomial::new(opening.lagrange_poly),
+        }
+    }
+}
+
+impl<E: Pairing> From<MultiOpening<E>> for SerializableMultiOpening<E> {
+    fn from(opening: MultiOpening<E>) -> Self {
+        SerializableMultiOpening {
+            commitment: opening.commitment,
+            points: opening.points,
+            values: opening.values,
+            pi: opening.pi,
+            zero_commitment: opening.zero_commitment,
+            lagrange_poly: opening.lagrange_poly.coeffs().to_vec(),
+        }
+    }
+}
+
+// a proof that the committed polynomial evaluates to value at point
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct OpeningProof<E: Pairing> {
+    pub point: E::ScalarField,
+    pub value: E::ScalarField,
+    pub pi: E::G1,
+}
+
+impl<E: PairingBackend> OpeningProof<E> {
+    pub fn verify(&self, vk: &VerifierKey<E>, commitment: &E::G1) -> bool {
+        vk.verify(self.point, self.value, *commitment, self.pi)
+    }
+}
+
+// a proof that the committed polynomial evaluates to values at points. the zero
+// commitment in G2 comes along since a verifier key only holds crs_g1, it is checked
+// against its G1 counterpart so a wrong one cannot make a false opening pass
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultiOpeningProof<E: Pairing> {
+    pub points: Vec<E::ScalarField>,
+    pub values: Vec<E::ScalarField>,
+    pub pi: E::G1,
+    pub zero_commitment: E::G2,
+}
+
+impl<E: PairingBackend> MultiOpeningProof<E> {
+    pub fn verify(&self, vk: &VerifierKey<E>, commitment: &E::G1) -> bool {
+        // interpolate directly, building a Lagrange basis only pays off when it is reused
+        let lagrange_poly = match Polynomial::from_evaluations(&self.points, &self.values) {
+            Ok(lagrange_poly) => lagrange_poly,
+            Err(_) => return false,
+        };
+
+        vk.verify_multi_with_hints(
+            &self.points,
+            &self.values,
+            *commitment,
+            self.pi,
+            self.zero_commitment,
+            &lagrange_poly
+        )
+    }
+
+    // the opening with the hints verify_multi_with_hints and the batch check take
+    pub fn to_multi_opening(&self, commitment: E::G1) -> Result<MultiOpening<E>, &'static str> {
+        if self.points.is_empty() {
+            return Err("No points given");
+        }
+        Ok(MultiOpening {
+            commitment,
+            points: self.points.clone(),
+            values: self.values.clone(),
+            pi: self.pi,
+            zero_commitment: self.zero_commitment,
+            lagrange_poly: Polynomial::from_evaluations(&self.points, &self.values)?,
+        })
+    }
 }
 
-impl <E:Pairing> KZG<E> {
+impl<E: Pairing> CurveCheck for OpeningProof<E>
+where
+    E::G1: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.pi.curve_check()
+    }
+}
+
+impl<E: Pairing> CurveCheck for MultiOpeningProof<E>
+where
+    E::G1: CurveCheck,
+    E::G2: CurveCheck,
+{
+    fn curve_check(&self) -> Result<(), DecodeError> {
+        self.pi.curve_check()?;
+        self.zero_commitment.curve_check()
+    }
+}
+
+// zero polynomial, its commitment in regards to crs_g2 and the Lagrange basis
+// of a set of evaluation points, see KZG::prepare_points
+#[derive(Clone, Debug)]
+pub struct PreparedPointSet<E: PairingBackend> {
+    pub context: LagrangeInterpContext<E::ScalarField>,
+    pub zero_poly: Polynomial<E::ScalarField>,
+    pub zero_commitment: E::G2,
+    pub zero_commitment_prepared: E::PreparedG2,
+}
+
+impl<E: PairingBackend> PreparedPointSet<E> {
+    pub fn points(&self) -> &[E::ScalarField] {
+        self.context.points()
+    }
+
+    fn proof(&self, values: Vec<E::ScalarField>, pi: E::G1) -> MultiOpeningProof<E> {
+        MultiOpeningProof {
+            points: self.points().to_vec(),
+            values,
+            pi,
+            zero_commitment: self.zero_commitment,
+        }
+    }
+}
+
+// crs_g1 in the Lagrange basis of a domain, [L_0(tau)], ..., [L_{n-1}(tau)],
+// optionally stored in bit-reversed order
+#[derive(Clone, Debug)]
+pub struct LagrangeCrs<E: Pairing> {
+    pub domain: Domain<E::ScalarField>,
+    pub bit_reversed: bool,
+    pub crs_g1: Vec<E::G1>,
+}
+
+impl <E: PairingBackend> KZG<E> {
     pub fn new(g1: E::G1, g2: E::G2, degree: usize) -> Self {
+        let g2_tau = g2.mul(E::ScalarField::ZERO);
         Self {
             g1,
             g2,
-            g2_tau: g2.mul(E::ScalarField::ZERO),
+            g2_tau,
             degree,
             crs_g1: vec![],
             crs_g2: vec![],
+            crs_lagrange: vec![],
+            vk: VerifierKey::new(g1, g2, g2_tau, vec![]),
         }
     }
 
@@ -30,98 +406,209 @@ impl <E:Pairing> KZG<E> {
             self.crs_g2.push(self.g2.mul(secret.pow(&[i as u64])));
         }
         self.g2_tau = self.g2.mul(secret);
+        self.vk = VerifierKey::new(self.g1, self.g2, self.g2_tau, self.crs_g1.clone());
+        // any Lagrange basis was derived from the previous crs
+        self.crs_lagrange.clear();
     }
 
-    pub fn commit(&self, poly: &[E::ScalarField]) -> E::G1 {
-        let mut commitment = self.g1.mul(E::ScalarField::ZERO);
-        for i in 0..self.degree+1 {
-            commitment += self.crs_g1[i] * poly[i];
+    // derive crs_g1 in the Lagrange basis of the domain of the given size and cache it.
+    // since L_i(x) = 1/n * sum_j omega^(-ij) x^j, the basis is the IFFT of crs_g1 in G1
+    pub fn setup_lagrange(&mut self, domain_size: usize, bit_reversed: bool) -> Result<(), &'static str> {
+        let domain = Domain::new(domain_size).ok_or("Domain is too large for the field")?;
+        if domain.size > self.crs_g1.len() {
+            return Err("Domain size exceeds the setup degree");
         }
-        commitment
+
+        let mut crs_g1 = domain.ifft(&self.crs_g1[..domain.size]);
+        if bit_reversed {
+            bit_reverse_permutation(&mut crs_g1);
+        }
+
+        self.crs_lagrange.retain(|lagrange| lagrange.domain.size != domain.size);
+        self.crs_lagrange.push(LagrangeCrs { domain, bit_reversed, crs_g1 });
+        Ok(())
+    }
+
+    // the basis set up for domain_size, rounded up to a power of two as in setup_lagrange
+    pub fn lagrange_crs(&self, domain_size: usize) -> Option<&LagrangeCrs<E>> {
+        let size = Domain::<E::ScalarField>::new(domain_size)?.size;
+        self.crs_lagrange.iter().find(|lagrange| lagrange.domain.size == size)
+    }
+
+    // commit to a polynomial in evaluation form with the cached Lagrange basis,
+    // equal to committing to the interpolated coefficients
+    pub fn commit_evaluations(&self, evals: &Evaluations<E::ScalarField>) -> Result<E::G1, &'static str> {
+        let lagrange = self
+            .lagrange_crs(evals.domain().size)
+            .ok_or("No Lagrange basis was set up for the domain")?;
+
+        if !lagrange.bit_reversed {
+            return evals.commit::<E>(&lagrange.crs_g1);
+        }
+
+        // the basis is in bit-reversed order while the evaluations are in natural order
+        let mut ordered = evals.evals().to_vec();
+        bit_reverse_permutation(&mut ordered);
+        Ok(E::msm(&lagrange.crs_g1, &ordered))
     }
 
-    pub fn open(&self, poly: &[E::ScalarField], point: E::ScalarField) -> E::G1 {
+    // open a polynomial in evaluation form at point without going back to coefficients
+    pub fn open_evaluations(
+        &self,
+        evals: &Evaluations<E::ScalarField>,
+        point: E::ScalarField
+    ) -> Result<OpeningProof<E>, &'static str> {
+        let (value, quotient) = evals.quotient(point);
+        Ok(OpeningProof { point, value, pi: self.commit_evaluations(&quotient)? })
+    }
+
+    pub fn commit(&self, poly: &Polynomial<E::ScalarField>) -> Result<E::G1, &'static str> {
+        if poly.coeffs().len() > self.crs_g1.len() {
+            return Err("Polynomial degree exceeds the setup degree");
+        }
+
+        Ok(E::msm(&self.crs_g1, poly.coeffs()))
+    }
+
+    pub fn open(&self, poly: &Polynomial<E::ScalarField>, point: E::ScalarField) -> Result<OpeningProof<E>, &'static str> {
         // evaluate the polynomial at point
-        let value = evaluate(poly, point);
+        let value = poly.evaluate(point);
 
         // initialize denominator
-        let denominator = [-point, E::ScalarField::ONE];
+        let denominator = Polynomial::from_roots(&[point]);
 
         // initialize numerator
-        let first = poly[0] - value;
-        let rest = &poly[1..];
-        let temp: Vec<E::ScalarField> = std::iter::once(first).chain(rest.iter().cloned()).collect();
-        let numerator: &[E::ScalarField] = &temp;
+        let numerator = poly - &Polynomial::new(vec![value]);
 
         // get quotient by dividing numerator by denominator
-        let quotient = div(numerator, &denominator).unwrap();
+        let (quotient, remainder) = numerator.div_rem(&denominator)?;
+        if !remainder.is_zero() {
+            return Err(NON_ZERO_REMAINDER);
+        }
 
         // calculate pi as proof (quotient multiplied by CRS)
-        let mut pi = self.g1.mul(E::ScalarField::ZERO);
-        for i in 0..quotient.len() {
-            pi += self.crs_g1[i] * quotient[i];
-        }
+        Ok(OpeningProof { point, value, pi: self.commit(&quotient)? })
+    }
+
+    pub fn multi_open_with_lagrange(
+        &self,
+        poly: &Polynomial<E::ScalarField>,
+        points: &[E::ScalarField],
+        lagrange: &Polynomial<E::ScalarField>
+    ) -> Result<E::G1, &'static str> {
+        // denominator is a polynomial where all its root are points to be evaluated (zero poly)
+        let zero_poly = Polynomial::from_roots(points);
 
-        // return pi
-        pi
+        self.open_with_zero_poly(poly, &zero_poly, lagrange)
     }
 
-    pub fn multi_open_with_lagrange(&self, poly: &[E::ScalarField], points: &[E::ScalarField], lagrange: &[E::ScalarField]) -> E::G1 {
+    pub fn multi_open(&self, poly: &Polynomial<E::ScalarField>, points: &[E::ScalarField]) -> Result<MultiOpeningProof<E>, &'static str> {
+        let prepared = self.prepare_points(points)?;
+        self.multi_open_prepared(poly, &prepared)
+    }
+
+    // multi_open reusing the precomputation of a point set across calls
+    pub fn multi_open_prepared(
+        &self,
+        poly: &Polynomial<E::ScalarField>,
+        prepared: &PreparedPointSet<E>
+    ) -> Result<MultiOpeningProof<E>, &'static str> {
         // perform Lagrange interpolation on points
-        
-        let mut lagrange_poly = lagrange.to_vec();
+        let values: Vec<E::ScalarField> = prepared.points().iter().map(|&point| poly.evaluate(point)).collect();
+        let lagrange_poly = prepared.context.interpolate(&values)?;
 
-        // numerator is the difference between the polynomial and the Lagrange interpolation
-        let mut numerator = Vec::with_capacity(poly.len());
-        for (coeff1, coeff2) in poly.iter().zip(lagrange_poly.as_slice()) {
-            numerator.push(*coeff1 - coeff2);
-        }
+        let pi = self.open_with_zero_poly(poly, &prepared.zero_poly, &lagrange_poly)?;
+        Ok(prepared.proof(values, pi))
+    }
 
-        // get quotient by dividing numerator by denominator
-        let quotient = div(&numerator, &[-points[0], E::ScalarField::ONE]).unwrap();
+    // open several polynomials at the same points, interpolating all of them with one basis
+    pub fn multi_open_many(
+        &self,
+        polys: &[Polynomial<E::ScalarField>],
+        prepared: &PreparedPointSet<E>
+    ) -> Result<Vec<MultiOpeningProof<E>>, &'static str> {
+        let points = prepared.points();
+        let values: Vec<Vec<E::ScalarField>> = polys
+            .iter()
+            .map(|poly| points.iter().map(|&point| poly.evaluate(point)).collect())
+            .collect();
+        let lagrange_polys = prepared.context.interpolate_many(&values)?;
 
-        // calculate pi as proof (quotient multiplied by CRS)
-        let mut pi = self.g1.mul(E::ScalarField::ZERO);
-        for i in 0..quotient.len() {
-            pi += self.crs_g1[i] * quotient[i];
-        }
+        polys
+            .iter()
+            .zip(values)
+            .zip(lagrange_polys.iter())
+            .map(|((poly, values), lagrange_poly)| {
+                let pi = self.open_with_zero_poly(poly, &prepared.zero_poly, lagrange_poly)?;
+                Ok(prepared.proof(values, pi))
+            })
+            .collect()
+    }
+
+    fn open_with_zero_poly(
+        &self,
+        poly: &Polynomial<E::ScalarField>,
+        zero_poly: &Polynomial<E::ScalarField>,
+        lagrange: &Polynomial<E::ScalarField>
+    ) -> Result<E::G1, &'static str> {
+        let quotient = Self::quotient_with_zero_poly(poly, zero_poly, lagrange)?;
 
-        // return pi
-        pi
+        // calculate pi as proof (quotient multiplied by CRS)
+        self.commit(&quotient)
     }
 
-    pub fn multi_open(&self, poly: &[E::ScalarField], points: &[E::ScalarField]) -> E::G1 {
-        // denominator is a polynomial where all its root are points to be evaluated (zero poly)
-        let mut zero_poly = vec![-points[0], E::ScalarField::ONE];
-        for i in 1..points.len() {
-            zero_poly = mul(&zero_poly, &[-points[i], E::ScalarField::ONE]);
-        }
+    // the quotient (p - I) / Z committed to by a multi-proof, which the
+    // quotient check mode of the verifier takes as a hint instead
+    pub fn multi_open_quotient(
+        &self,
+        poly: &Polynomial<E::ScalarField>,
+        points: &[E::ScalarField]
+    ) -> Result<Polynomial<E::ScalarField>, &'static str> {
+        let context = LagrangeInterpContext::new_from_points(points)?;
+        let values: Vec<E::ScalarField> = points.iter().map(|&point| poly.evaluate(point)).collect();
+        let lagrange_poly = context.interpolate(&values)?;
 
-        // perform Lagrange interpolation on points
-        let mut values = vec![];
-        for i in 0..points.len() {
-            values.push(evaluate(poly, points[i]));
-        }
-        let mut lagrange_poly = interpolate(points, &values).unwrap();
-        lagrange_poly.resize(poly.len(), E::ScalarField::ZERO); // pad with zeros
+        Self::quotient_with_zero_poly(poly, &Polynomial::from_roots(points), &lagrange_poly)
+    }
 
+    fn quotient_with_zero_poly(
+        poly: &Polynomial<E::ScalarField>,
+        zero_poly: &Polynomial<E::ScalarField>,
+        lagrange: &Polynomial<E::ScalarField>
+    ) -> Result<Polynomial<E::ScalarField>, &'static str> {
         // numerator is the difference between the polynomial and the Lagrange interpolation
-        let mut numerator = Vec::with_capacity(poly.len());
-        for (coeff1, coeff2) in poly.iter().zip(lagrange_poly.as_slice()) {
-            numerator.push(*coeff1 - coeff2);
-        }
+        let numerator = poly - lagrange;
 
         // get quotient by dividing numerator by denominator
-        let quotient = div(&numerator, &zero_poly).unwrap();
+        let (quotient, remainder) = numerator.div_rem(zero_poly)?;
+        if !remainder.is_zero() {
+            return Err(NON_ZERO_REMAINDER);
+        }
+        Ok(quotient)
+    }
 
-        // calculate pi as proof (quotient multiplied by CRS)
-        let mut pi = self.g1.mul(E::ScalarField::ZERO);
-        for i in 0..quotient.len() {
-            pi += self.crs_g1[i] * quotient[i];
+    // compute the zero polynomial, its commitment in regards to crs_g2 and the
+    // Lagrange basis of a point set once, so that openings and verifications
+    // at the same points only redo the value dependent part
+    pub fn prepare_points(&self, points: &[E::ScalarField]) -> Result<PreparedPointSet<E>, &'static str> {
+        let context = LagrangeInterpContext::new_from_points(points)?;
+
+        let zero_poly = Polynomial::from_roots(points);
+        if zero_poly.coeffs().len() > self.crs_g2.len() {
+            return Err("Number of points exceeds the setup degree");
+        }
+
+        let mut zero_commitment = self.g2.mul(E::ScalarField::ZERO);
+        for (i, coeff) in zero_poly.coeffs().iter().enumerate() {
+            zero_commitment += self.crs_g2[i] * coeff;
         }
 
-        // return pi
-        pi
+        Ok(PreparedPointSet {
+            context,
+            zero_poly,
+            zero_commitment,
+            zero_commitment_prepared: E::prepare_g2(zero_commitment),
+        })
     }
 
     pub fn verify(
@@ -131,9 +618,7 @@ impl <E:Pairing> KZG<E> {
         commitment: E::G1,
         pi: E::G1
     ) -> bool {
-        let lhs = E::pairing(pi, self.g2_tau - self.g2.mul(point));
-        let rhs = E::pairing(commitment - self.g1.mul(value), self.g2);
-        lhs == rhs
+        self.vk.verify(point, value, commitment, pi)
     }
 
     pub fn verify_multi(
@@ -143,29 +628,578 @@ impl <E:Pairing> KZG<E> {
         commitment: E::G1,
         pi: E::G1
     ) -> bool {
-        // compute the zero polynomial
-        let mut zero_poly = vec![-points[0], E::ScalarField::ONE];
-        for i in 1..points.len() {
-            zero_poly = mul(&zero_poly, &[-points[i], E::ScalarField::ONE]);
-        }
-
-        // compute commitment of zero polynomial in regards to crs_g2
-        let mut zero_commitment = self.g2.mul(E::ScalarField::ZERO);
-        for i in 0..zero_poly.len() {
-            zero_commitment += self.crs_g2[i] * zero_poly[i];
+        match self.prepare_points(points) {
+            Ok(prepared) => self.verify_multi_prepared(&prepared, values, commitment, pi),
+            Err(_) => false,
         }
+    }
 
+    // verify_multi reusing the precomputation of a point set across calls
+    pub fn verify_multi_prepared(
+        &self,
+        prepared: &PreparedPointSet<E>,
+        values: &[E::ScalarField],
+        commitment: E::G1,
+        pi: E::G1
+    ) -> bool {
         // compute lagrange polynomial
-        let lagrange_poly = interpolate(points, &values).unwrap();
+        let lagrange_poly = match prepared.context.interpolate(values) {
+            Ok(lagrange_poly) => lagrange_poly,
+            Err(_) => return false,
+        };
 
         // compute commitment of lagrange polynomial in regards to crs_g1
-        let mut lagrange_commitment = self.g1.mul(E::ScalarField::ZERO);
-        for i in 0..lagrange_poly.len() {
-            lagrange_commitment += self.crs_g1[i] * lagrange_poly[i];
+        // more points than the crs supports cannot have been opened
+        let lagrange_commitment = match self.commit(&lagrange_poly) {
+            Ok(lagrange_commitment) => lagrange_commitment,
+            Err(_) => return false,
+        };
+
+        // e(pi, zero_commitment) == e(commitment - lagrange_commitment, g2)
+        // checked as a single product of pairings against the identity
+        E::pairing_check(
+            &[pi, lagrange_commitment - commitment],
+            &[prepared.zero_commitment_prepared.clone(), self.vk.g2_prepared.clone()]
+        )
+    }
+
+    // verify_multi taking the zero polynomial commitment in G2 and the Lagrange
+    // polynomial as untrusted hints, see VerifierKey::verify_multi_with_hints
+    pub fn verify_multi_with_hints(
+        &self,
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        commitment: E::G1,
+        pi: E::G1,
+        zero_commitment: E::G2,
+        lagrange_poly: &Polynomial<E::ScalarField>
+    ) -> bool {
+        self.vk.verify_multi_with_hints(points, values, commitment, pi, zero_commitment, lagrange_poly)
+    }
+
+    // verify_multi_with_quotient with this setup, see VerifierKey::verify_multi_with_quotient
+    pub fn verify_multi_with_quotient(
+        &self,
+        points: &[E::ScalarField],
+        values: &[E::ScalarField],
+        commitment: E::G1,
+        poly: &Polynomial<E::ScalarField>,
+        quotient: &Polynomial<E::ScalarField>
+    ) -> bool {
+        self.vk.verify_multi_with_quotient(points, values, commitment, poly, quotient)
+    }
+
+    // batch_verify_multi_with_hints with this setup, see VerifierKey::batch_verify_multi_with_hints
+    pub fn batch_verify_multi_with_hints(&self, openings: &[MultiOpening<E>]) -> bool {
+        self.vk.batch_verify_multi_with_hints(openings)
+    }
+
+    // verifier key able to check multi-proofs over up to max_points points
+    pub fn verifier_key(&self, max_points: usize) -> Result<VerifierKey<E>, &'static str> {
+        if max_points + 1 > self.crs_g1.len() {
+            return Err("Number of points exceeds the setup degree");
         }
+        Ok(VerifierKey::new(self.g1, self.g2, self.g2_tau, self.crs_g1[..max_points + 1].to_vec()))
+    }
+
+    // verifier_key as sent to the guest, with the digest of the powers it leaves out
+    pub fn serializable_verifier_key(&self, max_points: usize) -> Result<SerializableVerifierKey<E>, &'static str> {
+        if max_points + 1 > self.crs_g1.len() {
+            return Err("Number of points exceeds the setup degree");
+        }
+        Ok(SerializableVerifierKey {
+            g1: self.g1,
+            g2: self.g2,
+            g2_tau: self.g2_tau,
+            crs_g1: self.crs_g1[..max_points + 1].to_vec(),
+            crs_g1_rest: crs_digest(&self.crs_g1[max_points + 1..], EMPTY_CRS_DIGEST),
+        })
+    }
 
-        let lhs = E::pairing(pi, zero_commitment);
-        let rhs = E::pairing(commitment - lagrange_commitment, self.g2);
-        lhs == rhs
+    // digest identifying this setup, as committed by the guest for any verifier key from it
+    pub fn setup_digest(&self) -> [u8; 32] {
+        setup_digest(&SerializableVerifierKey::from(&self.vk))
     }
-}
\ No newline at end of file
+}
+
+impl<E: PairingBackend + KnownCurve> KZG<E> {
+    // store the setup in an ObjectKind::Srs container, the Lagrange bases are not
+    // stored since setup_lagrange derives them from crs_g1
+    pub fn write_srs<W: Write>(&self, writer: W, compress: Compress) -> Result<(), ContainerError> {
+        let srs = SerializableSrs::<E> {
+            g1: self.g1,
+            g2: self.g2,
+            g2_tau: self.g2_tau,
+            crs_g1: self.crs_g1.clone(),
+            crs_g2: self.crs_g2.clone(),
+        };
+        write_container::<E, _, _>(writer, ObjectKind::Srs, &srs, compress)
+    }
+
+    // load a setup written by write_srs, its degree is given by the length of crs_g1
+    pub fn read_srs<R: Read>(reader: R, validation: ValidationMode) -> Result<Self, ContainerError>
+    where
+        E::G1: CurveCheck,
+        E::G2: CurveCheck,
+    {
+        let srs: SerializableSrs<E> = read_container::<E, _, _>(reader, ObjectKind::Srs, validation)?;
+        // crs_g1 and crs_g2 both hold the powers tau^0..tau^degree
+        if srs.crs_g1.is_empty() || srs.crs_g1.len() != srs.crs_g2.len() {
+            return Err(ContainerError::Decode(DecodeError::InvalidEncoding));
+        }
+        Ok(Self {
+            g1: srs.g1,
+            g2: srs.g2,
+            g2_tau: srs.g2_tau,
+            degree: srs.crs_g1.len() - 1,
+            vk: VerifierKey::new(srs.g1, srs.g2, srs.g2_tau, srs.crs_g1.clone()),
+            crs_g1: srs.crs_g1,
+            crs_g2: srs.crs_g2,
+            crs_lagrange: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective as G1, G2Projective as G2};
+    use ark_serialize::Validate;
+    use ark_std::UniformRand;
+
+    fn setup(degree: usize) -> KZG<Bls12_381> {
+        let mut rng = ark_std::test_rng();
+        let mut kzg = KZG::new(G1::rand(&mut rng), G2::rand(&mut rng), degree);
+        kzg.setup(Fr::rand(&mut rng));
+        kzg
+    }
+
+    fn random_poly(len: usize) -> Polynomial<Fr> {
+        let mut rng = ark_std::test_rng();
+        Polynomial::new((0..len).map(|_| Fr::rand(&mut rng)).collect())
+    }
+
+    #[test]
+    fn commit_evaluations_matches_commit() {
+        for bit_reversed in [false, true] {
+            let mut kzg = setup(16);
+            kzg.setup_lagrange(16, bit_reversed).unwrap();
+            let domain = kzg.lagrange_crs(16).unwrap().domain;
+
+            let poly = random_poly(16);
+            let evals = Evaluations::from_polynomial(&poly, domain).unwrap();
+            assert_eq!(kzg.commit_evaluations(&evals).unwrap(), kzg.commit(&poly).unwrap());
+        }
+    }
+
+    #[test]
+    fn lagrange_crs_rounds_the_size_up() {
+        let mut kzg = setup(16);
+        kzg.setup_lagrange(10, false).unwrap();
+        assert_eq!(kzg.lagrange_crs(10).unwrap().domain.size, 16);
+        assert_eq!(kzg.lagrange_crs(16).unwrap().domain.size, 16);
+        assert!(kzg.lagrange_crs(8).is_none());
+    }
+
+    #[test]
+    fn open_evaluations_matches_open() {
+        let mut kzg = setup(16);
+        kzg.setup_lagrange(16, false).unwrap();
+        let domain = kzg.lagrange_crs(16).unwrap().domain;
+        let poly = random_poly(16);
+        let evals = Evaluations::from_polynomial(&poly, domain).unwrap();
+
+        // outside the domain, then on a domain element where the quotient takes its special branch
+        let mut rng = ark_std::test_rng();
+        for point in [Fr::rand(&mut rng), domain.element(5)] {
+            let proof = kzg.open_evaluations(&evals, point).unwrap();
+            assert_eq!(proof, kzg.open(&poly, point).unwrap());
+            assert!(proof.verify(&kzg.vk, &kzg.commit(&poly).unwrap()));
+        }
+    }
+
+    #[test]
+    fn verify_checks_single_openings() {
+        let kzg = setup(16);
+        let mut rng = ark_std::test_rng();
+        let poly = random_poly(17);
+        let commitment = kzg.commit(&poly).unwrap();
+        let point = Fr::rand(&mut rng);
+        let proof = kzg.open(&poly, point).unwrap();
+
+        assert!(kzg.verify(point, proof.value, commitment, proof.pi));
+        assert!(proof.verify(&kzg.vk, &commitment));
+        assert!(!kzg.verify(point, proof.value + Fr::from(1u64), commitment, proof.pi));
+        assert!(!kzg.verify(point + Fr::from(1u64), proof.value, commitment, proof.pi));
+        assert!(!kzg.verify(point, proof.value, commitment + kzg.g1, proof.pi));
+        assert!(!kzg.verify(point, proof.value, commitment, proof.pi + kzg.g1));
+    }
+
+    #[test]
+    fn proofs_round_trip() {
+        let kzg = setup(16);
+        let mut rng = ark_std::test_rng();
+        let poly = random_poly(17);
+        let commitment = kzg.commit(&poly).unwrap();
+        let single = kzg.open(&poly, Fr::rand(&mut rng)).unwrap();
+        let points: Vec<Fr> = (0..6).map(|_| Fr::rand(&mut rng)).collect();
+        let multi = kzg.multi_open(&poly, &points).unwrap();
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut bytes = Vec::new();
+            single.serialize_with_mode(&mut bytes, compress).unwrap();
+            let read = OpeningProof::<Bls12_381>::deserialize_with_mode(&bytes[..], compress, Validate::Yes).unwrap();
+            assert_eq!(read, single);
+            assert!(read.verify(&kzg.vk, &commitment));
+
+            let mut bytes = Vec::new();
+            multi.serialize_with_mode(&mut bytes, compress).unwrap();
+            let read = MultiOpeningProof::<Bls12_381>::deserialize_with_mode(&bytes[..], compress, Validate::Yes).unwrap();
+            assert_eq!(read, multi);
+            assert!(read.verify(&kzg.vk, &commitment));
+        }
+    }
+
+    #[test]
+    fn multi_proof_with_repeated_points_is_rejected() {
+        let kzg = setup(16);
+        let poly = random_poly(17);
+        let commitment = kzg.commit(&poly).unwrap();
+        let mut rng = ark_std::test_rng();
+        let points: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+        let mut proof = kzg.multi_open(&poly, &points).unwrap();
+
+        proof.points.push(points[0]);
+        proof.values.push(proof.values[0]);
+        assert!(!proof.verify(&kzg.vk, &commitment));
+        assert!(proof.to_multi_opening(commitment).is_err());
+    }
+
+    #[test]
+    fn prepared_point_set_is_reused_across_polynomials() {
+        let kzg = setup(16);
+        let mut rng = ark_std::test_rng();
+        let points: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let prepared = kzg.prepare_points(&points).unwrap();
+        let polys: Vec<Polynomial<Fr>> = (0..3).map(|i| random_poly(17 - i)).collect();
+
+        let proofs = kzg.multi_open_many(&polys, &prepared).unwrap();
+        for (poly, proof) in polys.iter().zip(proofs.iter()) {
+            let commitment = kzg.commit(poly).unwrap();
+            assert_eq!(*proof, kzg.multi_open_prepared(poly, &prepared).unwrap());
+            assert_eq!(*proof, kzg.multi_open(poly, &points).unwrap());
+            assert!(kzg.verify_multi_prepared(&prepared, &proof.values, commitment, proof.pi));
+            assert!(kzg.verify_multi(&points, &proof.values, commitment, proof.pi));
+
+            let mut wrong_values = proof.values.clone();
+            wrong_values[2] += Fr::from(1u64);
+            assert!(!kzg.verify_multi_prepared(&prepared, &wrong_values, commitment, proof.pi));
+            assert!(!kzg.verify_multi(&points, &wrong_values, commitment, proof.pi));
+            assert!(!kzg.verify_multi_prepared(&prepared, &proof.values, commitment + kzg.g1, proof.pi));
+            assert!(!kzg.verify_multi_prepared(&prepared, &proof.values, commitment, proof.pi + kzg.g1));
+        }
+
+        // a proof for one polynomial does not open another
+        let other = kzg.commit(&polys[1]).unwrap();
+        assert!(!kzg.verify_multi_prepared(&prepared, &proofs[0].values, other, proofs[0].pi));
+    }
+
+    #[test]
+    fn verify_multi_with_hints_checks_the_hints() {
+        let kzg = setup(16);
+        let poly = random_poly(17);
+        let commitment = kzg.commit(&poly).unwrap();
+        let mut rng = ark_std::test_rng();
+        let points: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let opening = kzg.multi_open(&poly, &points).unwrap().to_multi_opening(commitment).unwrap();
+
+        let verify = |opening: &MultiOpening<Bls12_381>| {
+            kzg.verify_multi_with_hints(
+                &opening.points,
+                &opening.values,
+                opening.commitment,
+                opening.pi,
+                opening.zero_commitment,
+                &opening.lagrange_poly
+            )
+        };
+        assert!(verify(&opening));
+
+        let mut wrong_zero_commitment = opening.clone();
+        wrong_zero_commitment.zero_commitment += kzg.g2;
+        assert!(!verify(&wrong_zero_commitment));
+
+        let mut wrong_pi = opening.clone();
+        wrong_pi.pi += kzg.g1;
+        assert!(!verify(&wrong_pi));
+
+        let mut wrong_value = opening.clone();
+        wrong_value.values[0] += Fr::from(1u64);
+        assert!(!verify(&wrong_value));
+    }
+
+    #[test]
+    fn batch_rejects_a_single_bad_opening() {
+        let kzg = setup(16);
+        let vk = kzg.verifier_key(9).unwrap();
+        let mut rng = ark_std::test_rng();
+        let openings: Vec<MultiOpening<Bls12_381>> = [2, 5, 9]
+            .iter()
+            .map(|&count| {
+                let poly = Polynomial::new((0..17).map(|_| Fr::rand(&mut rng)).collect());
+                let points: Vec<Fr> = (0..count).map(|_| Fr::rand(&mut rng)).collect();
+                let commitment = kzg.commit(&poly).unwrap();
+                kzg.multi_open(&poly, &points).unwrap().to_multi_opening(commitment).unwrap()
+            })
+            .collect();
+        assert!(vk.batch_verify_multi_with_hints(&openings));
+        assert!(!vk.batch_verify_multi_with_hints(&[]));
+
+        // a false value with a Lagrange hint through it passes the hint checks, only the
+        // pairing product can catch it
+        for i in 0..openings.len() {
+            let mut batch = openings.clone();
+            batch[i].values[0] += Fr::from(1u64);
+            batch[i].lagrange_poly = Polynomial::from_evaluations(&batch[i].points, &batch[i].values).unwrap();
+            assert!(!vk.batch_verify_multi_with_hints(&batch));
+        }
+
+        let mut swapped = openings.clone();
+        swapped[0].pi = openings[1].pi;
+        swapped[1].pi = openings[0].pi;
+        assert!(!vk.batch_verify_multi_with_hints(&swapped));
+    }
+
+    #[test]
+    fn verify_multi_with_quotient_checks_the_identity() {
+        let kzg = setup(16);
+        let vk = kzg.verifier_key(16).unwrap();
+        let mut rng = ark_std::test_rng();
+        let poly = random_poly(17);
+        let commitment = kzg.commit(&poly).unwrap();
+        let points: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let values: Vec<Fr> = points.iter().map(|&point| poly.evaluate(point)).collect();
+        let quotient = kzg.multi_open_quotient(&poly, &points).unwrap();
+
+        assert!(vk.verify_multi_with_quotient(&points, &values, commitment, &poly, &quotient));
+
+        let mut wrong_values = values.clone();
+        wrong_values[1] += Fr::from(1u64);
+        assert!(!vk.verify_multi_with_quotient(&points, &wrong_values, commitment, &poly, &quotient));
+
+        // the hinted polynomial must be the committed one
+        let other = &poly + &Polynomial::new(vec![Fr::from(1u64)]);
+        let other_commitment = kzg.commit(&other).unwrap();
+        assert!(!vk.verify_multi_with_quotient(&points, &values, other_commitment, &poly, &quotient));
+
+        let tampered = &quotient + &Polynomial::new(vec![Fr::from(1u64)]);
+        assert!(!vk.verify_multi_with_quotient(&points, &values, commitment, &poly, &tampered));
+
+        // a key too short for the polynomial cannot recompute its commitment
+        let short = kzg.verifier_key(5).unwrap();
+        assert!(!short.verify_multi_with_quotient(&points, &values, commitment, &poly, &quotient));
+    }
+
+    #[test]
+    fn setup_digest_does_not_depend_on_the_prefix() {
+        let kzg = setup(16);
+        for max_points in [1, 4, 10, 16] {
+            let vk = kzg.serializable_verifier_key(max_points).unwrap();
+            assert_eq!(vk.crs_g1.len(), max_points + 1);
+            assert_eq!(setup_digest(&vk), kzg.setup_digest());
+        }
+        assert!(kzg.serializable_verifier_key(17).is_err());
+
+        // a tampered power in the prefix or the rest changes the digest
+        let mut vk = kzg.serializable_verifier_key(4).unwrap();
+        vk.crs_g1[2] += kzg.g1;
+        assert_ne!(setup_digest(&vk), kzg.setup_digest());
+        let mut vk = kzg.serializable_verifier_key(4).unwrap();
+        vk.crs_g1_rest[0] ^= 1;
+        assert_ne!(setup_digest(&vk), kzg.setup_digest());
+
+        // as does another setup
+        let mut rng = ark_std::test_rng();
+        let mut other = KZG::<Bls12_381>::new(kzg.g1, kzg.g2, 16);
+        other.setup(Fr::rand(&mut rng));
+        assert_ne!(other.setup_digest(), kzg.setup_digest());
+    }
+
+    #[test]
+    fn verify_multi_prepared_rejects_points_beyond_the_crs() {
+        // a point set prepared against a larger setup has a Lagrange polynomial too long to commit
+        let small = setup(4);
+        let large = setup(16);
+        let mut rng = ark_std::test_rng();
+        let points: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let values: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let prepared = large.prepare_points(&points).unwrap();
+        assert!(!small.verify_multi_prepared(&prepared, &values, small.g1, small.g1));
+    }
+
+    fn srs_bytes(kzg: &KZG<Bls12_381>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        kzg.write_srs(&mut bytes, Compress::Yes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn srs_round_trip() {
+        let kzg = setup(8);
+        let read = KZG::<Bls12_381>::read_srs(&srs_bytes(&kzg)[..], ValidationMode::Full).unwrap();
+        assert_eq!(read.degree, kzg.degree);
+        assert_eq!(read.crs_g1, kzg.crs_g1);
+        assert_eq!(read.crs_g2, kzg.crs_g2);
+        assert_eq!(read.g2_tau, kzg.g2_tau);
+    }
+
+    #[test]
+    fn read_srs_rejects_mismatched_crs_lengths() {
+        let kzg = setup(4);
+        let srs = SerializableSrs::<Bls12_381> {
+            g1: kzg.g1,
+            g2: kzg.g2,
+            g2_tau: kzg.g2_tau,
+            crs_g1: kzg.crs_g1.clone(),
+            crs_g2: kzg.crs_g2[..2].to_vec(),
+        };
+        let mut bytes = Vec::new();
+        write_container::<Bls12_381, _, _>(&mut bytes, ObjectKind::Srs, &srs, Compress::Yes).unwrap();
+        assert!(matches!(
+            KZG::<Bls12_381>::read_srs(&bytes[..], ValidationMode::Full),
+            Err(ContainerError::Decode(DecodeError::InvalidEncoding))
+        ));
+    }
+
+    #[cfg(feature = "recursion")]
+    mod bls12_377 {
+        use super::*;
+        use ark_bls12_377::{Bls12_377, Fr, G1Projective as G1, G2Projective as G2};
+        use lib::container::CurveId;
+        use lib::{Ark, ArkCompressed};
+
+        fn setup(degree: usize) -> KZG<Bls12_377> {
+            let mut rng = ark_std::test_rng();
+            let mut kzg = KZG::new(G1::rand(&mut rng), G2::rand(&mut rng), degree);
+            kzg.setup(Fr::rand(&mut rng));
+            kzg
+        }
+
+        #[test]
+        fn points_round_trip() {
+            let mut rng = ark_std::test_rng();
+            let g1 = G1::rand(&mut rng);
+            let g2 = G2::rand(&mut rng);
+            assert_eq!(Ark::<G1>::try_from(&Ark(g1).to_bytes()[..]).unwrap().0, g1);
+            assert_eq!(ArkCompressed::<G1>::try_from(&ArkCompressed(g1).to_bytes()[..]).unwrap().0, g1);
+            assert_eq!(Ark::<G2>::try_from(&Ark(g2).to_bytes()[..]).unwrap().0, g2);
+            assert_eq!(ArkCompressed::<G2>::try_from(&ArkCompressed(g2).to_bytes()[..]).unwrap().0, g2);
+        }
+
+        #[test]
+        fn srs_round_trip() {
+            let kzg = setup(8);
+            let mut bytes = Vec::new();
+            kzg.write_srs(&mut bytes, Compress::Yes).unwrap();
+            let read = KZG::<Bls12_377>::read_srs(&bytes[..], ValidationMode::Full).unwrap();
+            assert_eq!(read.degree, kzg.degree);
+            assert_eq!(read.crs_g1, kzg.crs_g1);
+            assert_eq!(read.crs_g2, kzg.crs_g2);
+            assert_eq!(read.g2_tau, kzg.g2_tau);
+        }
+
+        #[test]
+        fn read_srs_rejects_a_bls12_381_container() {
+            let bytes = srs_bytes(&super::setup(4));
+            assert!(matches!(
+                KZG::<Bls12_377>::read_srs(&bytes[..], ValidationMode::Full),
+                Err(ContainerError::CurveMismatch { expected: CurveId::Bls12_377, found: CurveId::Bls12_381 })
+            ));
+        }
+
+        #[test]
+        fn open_and_verify() {
+            let kzg = setup(16);
+            let mut rng = ark_std::test_rng();
+            let poly = Polynomial::new((0..17).map(|_| Fr::rand(&mut rng)).collect());
+            let commitment = kzg.commit(&poly).unwrap();
+
+            let proof = kzg.open(&poly, Fr::rand(&mut rng)).unwrap();
+            assert!(proof.verify(&kzg.vk, &commitment));
+
+            let points: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+            let proof = kzg.multi_open(&poly, &points).unwrap();
+            assert!(proof.verify(&kzg.vk, &commitment));
+            assert!(!proof.verify(&kzg.vk, &(commitment + kzg.g1)));
+        }
+    }
+
+    #[cfg(feature = "bn254")]
+    mod bn254 {
+        use super::*;
+        use ark_bn254::{Bn254, Fr, G1Projective as G1, G2Projective as G2};
+        use lib::container::CurveId;
+
+        fn setup(degree: usize) -> KZG<Bn254> {
+            let mut rng = ark_std::test_rng();
+            let mut kzg = KZG::new(G1::rand(&mut rng), G2::rand(&mut rng), degree);
+            kzg.setup(Fr::rand(&mut rng));
+            kzg
+        }
+
+        #[test]
+        fn srs_round_trip() {
+            let kzg = setup(8);
+            for compress in [Compress::Yes, Compress::No] {
+                let mut bytes = Vec::new();
+                kzg.write_srs(&mut bytes, compress).unwrap();
+                let read = KZG::<Bn254>::read_srs(&bytes[..], ValidationMode::Full).unwrap();
+                assert_eq!(read.degree, kzg.degree);
+                assert_eq!(read.crs_g1, kzg.crs_g1);
+                assert_eq!(read.crs_g2, kzg.crs_g2);
+                assert_eq!(read.g2_tau, kzg.g2_tau);
+            }
+        }
+
+        #[test]
+        fn read_srs_rejects_a_bls12_381_container() {
+            let bytes = srs_bytes(&super::setup(4));
+            assert!(matches!(
+                KZG::<Bn254>::read_srs(&bytes[..], ValidationMode::Full),
+                Err(ContainerError::CurveMismatch { expected: CurveId::Bn254, found: CurveId::Bls12_381 })
+            ));
+        }
+
+        #[test]
+        fn open_and_verify() {
+            let kzg = setup(16);
+            let mut rng = ark_std::test_rng();
+            let poly = Polynomial::new((0..17).map(|_| Fr::rand(&mut rng)).collect());
+            let commitment = kzg.commit(&poly).unwrap();
+
+            let point = Fr::rand(&mut rng);
+            let proof = kzg.open(&poly, point).unwrap();
+            assert_eq!(proof.value, poly.evaluate(point));
+            assert!(proof.verify(&kzg.vk, &commitment));
+            assert!(!kzg.verify(point, proof.value + Fr::from(1u64), commitment, proof.pi));
+        }
+
+        #[test]
+        fn multi_open_and_verify() {
+            let kzg = setup(16);
+            let mut rng = ark_std::test_rng();
+            let poly = Polynomial::new((0..17).map(|_| Fr::rand(&mut rng)).collect());
+            let commitment = kzg.commit(&poly).unwrap();
+
+            let points: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+            let proof = kzg.multi_open(&poly, &points).unwrap();
+            assert!(proof.verify(&kzg.vk, &commitment));
+            assert!(kzg.verify_multi(&points, &proof.values, commitment, proof.pi));
+            assert!(!kzg.verify_multi(&points, &proof.values, commitment + kzg.g1, proof.pi));
+
+            let mut wrong_values = proof.values.clone();
+            wrong_values[0] += Fr::from(1u64);
+            assert!(!kzg.verify_multi(&points, &wrong_values, commitment, proof.pi));
+        }
+    }
+}
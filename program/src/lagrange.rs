@@ -1,52 +1,54 @@
-use ark_ff::FftField;
-use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
-use ark_std::{vec, vec::Vec};
-use core::ops::Mul;
+use ark_ff::{batch_inversion, FftField};
 
-use crate::cfg_iter;
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
-
-use crate::Error;
+use crate::polynomial::Polynomial;
 
 #[derive(Debug, Clone)]
-pub(crate) struct LagrangeInterpContext<F: FftField> {
-    lag_polys: Vec<DensePolynomial<F>>,
+pub struct LagrangeInterpContext<F: FftField> {
+    points: Vec<F>,
+    lag_polys: Vec<Polynomial<F>>,
 }
 
 impl<F: FftField> LagrangeInterpContext<F> {
-    pub fn new_from_points(points: &[F]) -> Result<Self, Error> {
+    pub fn new_from_points(points: &[F]) -> Result<Self, &'static str> {
+        if points.is_empty() {
+            return Err("No points given");
+        }
+
         // Generate the non-normalized lagrange polynomials. These are zero on all points other
         // than the target point, and some nonzero value on that point.
-        let non_normalized_polys: Vec<_> = cfg_iter!(points)
+        let non_normalized_polys: Vec<_> = points
+            .iter()
+            .enumerate()
             .map(|(j, _x_j)| {
-                let mut l_poly: DensePolynomial<F> =
-                    DensePolynomial::from_coefficients_vec(vec![F::one()]);
-                for (k, x_k) in points.iter().enumerate() {
-                    if j == k {
-                        continue;
-                    }
-                    let tmp_poly: DensePolynomial<F> =
-                        DensePolynomial::from_coefficients_vec(vec![-(*x_k), F::one()]);
-                    // This does fft mul... not sure if it's actually faster
-                    l_poly = l_poly.mul(&tmp_poly);
-                }
-                l_poly
+                let others: Vec<F> = points
+                    .iter()
+                    .enumerate()
+                    .filter(|(k, _)| *k != j)
+                    .map(|(_, x_k)| *x_k)
+                    .collect();
+                Polynomial::from_roots(&others)
             })
             .collect();
 
-        let lag_polys = cfg_iter!(non_normalized_polys)
+        let lag_polys = non_normalized_polys
+            .iter()
+            .enumerate()
             .map(|(i, non_normed)| {
                 // Evaluate at target, divide by result
-                // so that the polynomial evaluates to 1 at the target
+                // so that the polynomial evaluates to 1 at the target.
+                // A zero here means the target appears twice in points
                 non_normed
-                    .evaluate(&points[i])
+                    .evaluate(points[i])
                     .inverse()
-                    .ok_or(Error::DivisorIsZero)
-                    .map(|v| non_normed * v)
+                    .ok_or("Points must be distinct")
+                    .map(|v| non_normed * &Polynomial::new(vec![v]))
             })
-            .collect::<Result<Vec<_>, Error>>()?;
-        Ok(Self { lag_polys })
+            .collect::<Result<Vec<_>, &'static str>>()?;
+        Ok(Self { points: points.to_vec(), lag_polys })
+    }
+
+    pub fn points(&self) -> &[F] {
+        &self.points
     }
 
     /// Given evals $((y_{1, 1}, \ldots y_{1_k}), \ldots (y_{l, 1}, \ldots y_{l, k}))$, points
@@ -57,22 +59,125 @@ impl<F: FftField> LagrangeInterpContext<F> {
         &self,
         evals: &[impl AsRef<[F]>],
         scalars: &[F],
-    ) -> Result<DensePolynomial<F>, Error> {
-        let mut targets = vec![F::zero(); self.lag_polys.len()];
+    ) -> Result<Polynomial<F>, &'static str> {
+        if evals.len() != scalars.len() {
+            return Err("Number of evaluations and scalars do not match");
+        }
+
+        let mut targets = vec![F::ZERO; self.lag_polys.len()];
         for i in 0..evals.len() {
             let eval = evals[i].as_ref();
+            if eval.len() != self.points.len() {
+                return Err("Number of points and values do not match");
+            }
             for j in 0..eval.len() {
                 // Our target at x_j is \sum gamma_i * y_{i, j}
-                // Does this as_ref() call introduce any overhead?
                 targets[j] += scalars[i] * eval[j];
             }
         }
         // Now we just interpolate to targets
-        targets
+        Ok(targets
             .into_iter()
             .enumerate()
-            .map(|(j, target)| self.lag_polys[j].mul(target))
-            .reduce(|x, y| x + y)
-            .ok_or(Error::NoPointsGiven)
+            .map(|(j, target)| &self.lag_polys[j] * &Polynomial::new(vec![target]))
+            .fold(Polynomial::zero(), |x, y| x + y))
+    }
+
+    // the unique polynomial of degree < k going through (x_j, values[j])
+    pub fn interpolate(&self, values: &[F]) -> Result<Polynomial<F>, &'static str> {
+        self.lagrange_interp_linear_combo(&[values], &[F::ONE])
+    }
+
+    // interpolate several sets of values over the same points, reusing the basis
+    pub fn interpolate_many(&self, evals: &[impl AsRef<[F]>]) -> Result<Vec<Polynomial<F>>, &'static str> {
+        evals
+            .iter()
+            .map(|values| self.interpolate(values.as_ref()))
+            .collect()
+    }
+}
+
+// evaluate the unique polynomial of degree < k going through (points[j], values[j]) at point,
+// in O(k^2) without building it: I(point) = sum_j values[j] * prod_{k != j} (point - x_k) / (x_j - x_k)
+pub fn evaluate_interpolation<F: FftField>(points: &[F], values: &[F], point: F) -> Result<F, &'static str> {
+    if points.len() != values.len() {
+        return Err("Number of points and values do not match");
+    }
+
+    let mut denominators: Vec<F> = points
+        .iter()
+        .enumerate()
+        .map(|(j, &x_j)| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(k, _)| *k != j)
+                .fold(F::ONE, |acc, (_, &x_k)| acc * (x_j - x_k))
+        })
+        .collect();
+    if denominators.iter().any(|denominator| denominator.is_zero()) {
+        return Err("Points must be distinct");
+    }
+    batch_inversion(&mut denominators);
+
+    let mut result = F::ZERO;
+    for j in 0..points.len() {
+        let numerator = points
+            .iter()
+            .enumerate()
+            .filter(|(k, _)| *k != j)
+            .fold(F::ONE, |acc, (_, &x_k)| acc * (point - x_k));
+        result += values[j] * numerator * denominators[j];
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_std::rand::Rng;
+    use ark_std::UniformRand;
+
+    fn random_values(len: usize, rng: &mut impl Rng) -> Vec<Fr> {
+        (0..len).map(|_| Fr::rand(rng)).collect()
+    }
+
+    #[test]
+    fn interpolate_matches_from_evaluations() {
+        let mut rng = ark_std::test_rng();
+        let points = random_values(8, &mut rng);
+        let context = LagrangeInterpContext::new_from_points(&points).unwrap();
+        let evals: Vec<Vec<Fr>> = (0..3).map(|_| random_values(8, &mut rng)).collect();
+
+        let interpolated = context.interpolate_many(&evals).unwrap();
+        for (values, poly) in evals.iter().zip(interpolated.iter()) {
+            let expected = Polynomial::from_evaluations(&points, values).unwrap();
+            assert_eq!(context.interpolate(values).unwrap(), expected);
+            assert_eq!(*poly, expected);
+        }
+    }
+
+    #[test]
+    fn duplicate_points_are_rejected() {
+        let mut rng = ark_std::test_rng();
+        let mut points = random_values(4, &mut rng);
+        points[3] = points[1];
+        let values = random_values(4, &mut rng);
+
+        assert_eq!(LagrangeInterpContext::new_from_points(&points).unwrap_err(), "Points must be distinct");
+        assert_eq!(evaluate_interpolation(&points, &values, Fr::rand(&mut rng)).unwrap_err(), "Points must be distinct");
+    }
+
+    #[test]
+    fn evaluate_interpolation_matches_interpolate() {
+        let mut rng = ark_std::test_rng();
+        let points = random_values(6, &mut rng);
+        let values = random_values(6, &mut rng);
+        let poly = LagrangeInterpContext::new_from_points(&points).unwrap().interpolate(&values).unwrap();
+
+        let r = Fr::rand(&mut rng);
+        assert_eq!(evaluate_interpolation(&points, &values, r).unwrap(), poly.evaluate(r));
+        assert_eq!(evaluate_interpolation(&points, &values, points[2]).unwrap(), values[2]);
     }
 }
@@ -0,0 +1,21 @@
+//! KZG commitments and the verifiers the zkVM program runs, also usable natively
+//! e.g. by the examples.
+
+pub mod kzg;
+
+pub mod utils;
+
+pub mod fft;
+
+pub mod polynomial;
+
+pub mod evaluations;
+
+pub mod lagrange;
+
+pub mod backend;
+
+pub mod transcript;
+
+#[cfg(feature = "precompiles")]
+pub mod accel;
@@ -3,85 +3,115 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-pub mod kzg;
-
-pub mod utils;
-use kzg::KZG;
+use multiproof::kzg::{MultiOpening, VerifierKey};
+use multiproof::polynomial::Polynomial;
 
 use std::ops::Mul;
-use ark_std::UniformRand;
-use ark_bls12_381::{Bls12_381, Fr, G1Projective as G1, G2Projective as G2};
 use rand::seq::IteratorRandom;
 use ark_ff::Field;
 use ark_ec::pairing::Pairing;
-use lib::{SerdeSerializableCommitment, SerdeSerializableG2Commitment, SerdeSerializableLagrangePolynomial, SerdeSerializablePoints, SerdeSerializablePolynomial, SerializableCommitment, SerializableG2Commitment, SerializableLagrangePolynomial, SerializablePoints, SerializablePolynomial};
+use lib::{merkle_root, setup_digest, statement_digest, statement_leaf, Ark, ArkCompressed, BatchOutput, GuestInput, GuestOutput, GuestStatement, StatementOutput, ValidationMode};
 
+// the curve this guest verifies openings over, one ELF is built per curve
+#[cfg(not(feature = "bn254"))]
+type Curve = ark_bls12_381::Bls12_381;
+#[cfg(feature = "bn254")]
+type Curve = ark_bn254::Bn254;
 
 pub fn main() {
-    // NOTE:  values of n larger than 186 will overflow the u128 type,
-    // resulting in output that doesn't match fibonacci sequence.
-    // However, the resulting proof will still be valid!
     println!("Inside main");
 
-    // let 
+    // the host sends a single GuestInput: the verifier key and the statement with the
+    // hints of its verification mode, the guest only verifies
     println!("cycle-tracker-start: loading");
-    let n: u32 = sp1_zkvm::io::read();
-    let serializable_pvk = sp1_zkvm::io::read::<SerdeSerializablePoints>();
-    let pointsss = SerializablePoints::from(serializable_pvk).0;
-    let serializable_poly = sp1_zkvm::io::read::<SerdeSerializablePolynomial>();
-    let polyyy = SerializablePolynomial::from(serializable_poly).0;
-    let serializable_lag_poly = sp1_zkvm::io::read::<SerdeSerializableLagrangePolynomial>();
-    let lag_poly = SerializableLagrangePolynomial::from(serializable_lag_poly).0;
-    let seriablizable_commitment = sp1_zkvm::io::read::<SerdeSerializableCommitment>();
-    let commitmentt = SerializableCommitment::from(seriablizable_commitment).0;
-    let serializable_zero_comm = sp1_zkvm::io::read::<SerdeSerializableG2Commitment>();
-    let zero_comm = SerializableG2Commitment::from(serializable_zero_comm).0;
-    
-    // let p2 = sp1_zkvm::io::read();
-
-    let mut rng = ark_std::test_rng();
-    let degree = 16;
-    let mut kzg_instance = KZG::<Bls12_381>::new(
-        G1::rand(&mut rng),
-        G2::rand(&mut rng),
-        degree
-    );
-
-    // trusted setup ceremony
-    let secret = Fr::rand(&mut rng);
-    kzg_instance.setup(secret);
-
-    // generate a random polynomial and commit it
-    let poly = polyyy;
-    let commitment = commitmentt;
-
-    // generate three random points and open the polynomial at those points
-    let points: Vec<Fr> = pointsss;
-    let pi = kzg_instance.multi_open(&poly, &points);
-
-    // evaluate the polynomial at those points
-    let mut values = vec![];
-    for i in 0..points.len() {
-        values.push(utils::evaluate(&poly, points[i]));
+    // every point is checked to be on the curve and in the subgroup, unless the guest
+    // is built with the trusted-input feature for inputs from a trusted host
+    let validation = if cfg!(feature = "trusted-input") {
+        ValidationMode::Trusted
+    } else {
+        ValidationMode::Full
+    };
+    let input = match Ark::<GuestInput<Curve>>::decode(&sp1_zkvm::io::read_vec(), validation) {
+        Ok(input) => input.0,
+        Err(err) => panic!("Invalid guest input: {}", err),
+    };
+    if let Err(err) = input.validate() {
+        panic!("Invalid guest input: {}", err);
     }
+    let mode = input.statement.mode();
+    let vk_digest = setup_digest(&input.verifier_key);
+    let crs_g1_len = input.verifier_key.crs_g1.len() as u64;
+    let vk = VerifierKey::<Curve>::from(input.verifier_key);
+    println!("cycle-tracker-end: loading");
 
-    // verify the proof
-    assert!(kzg_instance.verify_multi(&points, &values, commitment, pi));
+    let (commitment, points, values, verified) = match input.statement {
+        GuestStatement::Pairing(opening) => {
+            let opening = MultiOpening::from(opening);
+
+            // verify the proof, using the zero commitment and Lagrange polynomial
+            // computed by the host as hints instead of recomputing them
+            println!("cycle-tracker-start: verification");
+            let verified = vk.verify_multi_with_hints(
+                &opening.points,
+                &opening.values,
+                opening.commitment,
+                opening.pi,
+                opening.zero_commitment,
+                &opening.lagrange_poly
+            );
+            println!("cycle-tracker-end: verification");
+            (opening.commitment, opening.points, opening.values, verified)
+        }
+        GuestStatement::QuotientCheck(opening) => {
+            let poly = Polynomial::new(opening.poly);
+            let quotient = Polynomial::new(opening.quotient);
+
+            // recompute the commitment and check the quotient identity at a random point,
+            // no pairing is needed but the verifier key must cover the degree of poly
+            println!("cycle-tracker-start: verification");
+            let verified = vk.verify_multi_with_quotient(&opening.points, &opening.values, opening.commitment, &poly, &quotient);
+            println!("cycle-tracker-end: verification");
+            (opening.commitment, opening.points, opening.values, verified)
+        }
+        GuestStatement::Batch(openings) => {
+            let openings: Vec<MultiOpening<Curve>> = openings.into_iter().map(MultiOpening::from).collect();
+            return verify_batch(&vk, vk_digest, crs_g1_len, &openings);
+        }
+    };
+
+    println!("Multi points evaluation verified: {}", verified);
+
+    // commit the statement and its outcome, i.e. "commitment opens to values at points"
+    sp1_zkvm::io::commit(&GuestOutput::<Curve>::Statement(StatementOutput {
+        setup_digest: vk_digest,
+        crs_g1_len,
+        commitment: ArkCompressed(commitment),
+        statement_digest: statement_digest(&points, &values),
+        mode,
+        verified,
+    }));
+}
 
-    println!("Multi points evaluation verified!");
-    let mut a: u128 = 0;
-    let mut b: u128 = 1;
-    let mut sum: u128;
-    println!("cycle-tracker-end: loading");
+// the batch mode checks all openings, each with its own proof and hints, with one
+// randomized pairing product and commits a Merkle root over the statements
+fn verify_batch(vk: &VerifierKey<Curve>, vk_digest: [u8; 32], crs_g1_len: u64, openings: &[MultiOpening<Curve>]) {
     println!("cycle-tracker-start: verification");
-    // for _ in 1..n {
-    //     sum = a + b;
-    //     a = b;
-    //     b = sum;
-    // }
-
-    sp1_zkvm::io::commit(&a);
-    sp1_zkvm::io::commit(&b);
+    let verified = vk.batch_verify_multi_with_hints(openings);
+    println!("cycle-tracker-end: verification");
+
+    println!("Batch of {} multi points evaluations verified: {}", openings.len(), verified);
+
+    let leaves: Vec<[u8; 32]> = openings
+        .iter()
+        .map(|opening| statement_leaf(&opening.commitment, &opening.points, &opening.values))
+        .collect();
+    sp1_zkvm::io::commit(&GuestOutput::<Curve>::Batch(BatchOutput {
+        setup_digest: vk_digest,
+        crs_g1_len,
+        statement_count: openings.len() as u64,
+        statements_root: merkle_root(&leaves),
+        verified,
+    }));
 }
 
 // for 3 points cyplrs = 1,298,386,543 cycles
\ No newline at end of file
@@ -0,0 +1,266 @@
+use std::ops::{Add, Div, Mul, Rem, Sub};
+use ark_ff::FftField;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate, Write,
+};
+use crate::utils::{add, div_rem, interpolate, mul};
+
+// dense univariate polynomial in coefficient form, lowest degree first.
+// trailing zero coefficients are always stripped, so the zero polynomial has no coefficients
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polynomial<F: FftField> {
+    coeffs: Vec<F>,
+}
+
+impl<F: FftField> Polynomial<F> {
+    pub fn new(coeffs: Vec<F>) -> Self {
+        let mut poly = Self { coeffs };
+        poly.normalize();
+        poly
+    }
+
+    pub fn zero() -> Self {
+        Self { coeffs: vec![] }
+    }
+
+    // polynomial whose roots are exactly the given points, i.e. (x - r_0)(x - r_1)...
+    pub fn from_roots(roots: &[F]) -> Self {
+        let mut coeffs = vec![F::ONE];
+        for root in roots {
+            coeffs = mul(&coeffs, &[-*root, F::ONE]);
+        }
+        Self::new(coeffs)
+    }
+
+    // unique polynomial of degree < points.len() going through (points[i], values[i])
+    pub fn from_evaluations(points: &[F], values: &[F]) -> Result<Self, &'static str> {
+        Ok(Self::new(interpolate(points, values)?))
+    }
+
+    pub fn coeffs(&self) -> &[F] {
+        &self.coeffs
+    }
+
+    pub fn into_coeffs(self) -> Vec<F> {
+        self.coeffs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    // degree of the polynomial, the zero polynomial is reported as degree 0
+    pub fn degree(&self) -> usize {
+        self.coeffs.len().saturating_sub(1)
+    }
+
+    // evaluate the polynomial at point with Horner's rule
+    pub fn evaluate(&self, point: F) -> F {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(F::ZERO, |acc, &coeff| acc * point + coeff)
+    }
+
+    // divide by another polynomial, returning the quotient and the remainder
+    pub fn div_rem(&self, divisor: &Self) -> Result<(Self, Self), &'static str> {
+        let (quotient, remainder) = div_rem(&self.coeffs, &divisor.coeffs)?;
+        Ok((Self::new(quotient), Self::new(remainder)))
+    }
+
+    fn normalize(&mut self) {
+        while let Some(true) = self.coeffs.last().map(|x| *x == F::ZERO) {
+            self.coeffs.pop();
+        }
+    }
+}
+
+impl<F: FftField> From<Vec<F>> for Polynomial<F> {
+    fn from(coeffs: Vec<F>) -> Self {
+        Self::new(coeffs)
+    }
+}
+
+impl<F: FftField> Valid for Polynomial<F> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.coeffs.check()
+    }
+}
+
+// serialized as its coefficients, so it can be sent wrapped in lib::Ark
+impl<F: FftField> CanonicalSerialize for Polynomial<F> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.coeffs.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.coeffs.serialized_size(compress)
+    }
+}
+
+impl<F: FftField> CanonicalDeserialize for Polynomial<F> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self::new(Vec::<F>::deserialize_with_mode(reader, compress, validate)?))
+    }
+}
+
+impl<'a, F: FftField> Add<&'a Polynomial<F>> for &'a Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn add(self, other: &'a Polynomial<F>) -> Polynomial<F> {
+        Polynomial::new(add(&self.coeffs, &other.coeffs))
+    }
+}
+
+impl<'a, F: FftField> Sub<&'a Polynomial<F>> for &'a Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn sub(self, other: &'a Polynomial<F>) -> Polynomial<F> {
+        let mut result = self.coeffs.clone();
+        result.resize(std::cmp::max(self.coeffs.len(), other.coeffs.len()), F::ZERO);
+        for (i, &coeff) in other.coeffs.iter().enumerate() {
+            result[i] -= coeff;
+        }
+        Polynomial::new(result)
+    }
+}
+
+impl<'a, F: FftField> Mul<&'a Polynomial<F>> for &'a Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn mul(self, other: &'a Polynomial<F>) -> Polynomial<F> {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        Polynomial::new(mul(&self.coeffs, &other.coeffs))
+    }
+}
+
+// panics on division by the zero polynomial, use `div_rem` to handle it
+impl<'a, F: FftField> Div<&'a Polynomial<F>> for &'a Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn div(self, other: &'a Polynomial<F>) -> Polynomial<F> {
+        self.div_rem(other).expect("Polynomial division failed").0
+    }
+}
+
+// panics on division by the zero polynomial, use `div_rem` to handle it
+impl<'a, F: FftField> Rem<&'a Polynomial<F>> for &'a Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn rem(self, other: &'a Polynomial<F>) -> Polynomial<F> {
+        self.div_rem(other).expect("Polynomial division failed").1
+    }
+}
+
+// owned operands forward to the by-reference implementations
+macro_rules! impl_owned_op {
+    ($trait:ident, $method:ident) => {
+        impl<F: FftField> $trait<Polynomial<F>> for Polynomial<F> {
+            type Output = Polynomial<F>;
+
+            fn $method(self, other: Polynomial<F>) -> Polynomial<F> {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+impl_owned_op!(Add, add);
+impl_owned_op!(Sub, sub);
+impl_owned_op!(Mul, mul);
+impl_owned_op!(Div, div);
+impl_owned_op!(Rem, rem);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_std::rand::Rng;
+    use ark_std::UniformRand;
+
+    fn random_poly(len: usize, rng: &mut impl Rng) -> Polynomial<Fr> {
+        Polynomial::new((0..len).map(|_| Fr::rand(rng)).collect())
+    }
+
+    #[test]
+    fn trailing_zeros_are_stripped() {
+        let poly = Polynomial::new(vec![Fr::from(1u64), Fr::from(2u64), Fr::from(0u64), Fr::from(0u64)]);
+        assert_eq!(poly.coeffs(), &[Fr::from(1u64), Fr::from(2u64)]);
+        assert_eq!(poly.degree(), 1);
+
+        let zero = Polynomial::new(vec![Fr::from(0u64); 3]);
+        assert!(zero.is_zero());
+        assert_eq!(zero, Polynomial::zero());
+    }
+
+    #[test]
+    fn arithmetic_matches_evaluation() {
+        let mut rng = ark_std::test_rng();
+        let a = random_poly(9, &mut rng);
+        let b = random_poly(4, &mut rng);
+        let r = Fr::rand(&mut rng);
+
+        assert_eq!((&a + &b).evaluate(r), a.evaluate(r) + b.evaluate(r));
+        assert_eq!((&a - &b).evaluate(r), a.evaluate(r) - b.evaluate(r));
+        assert_eq!((&a * &b).evaluate(r), a.evaluate(r) * b.evaluate(r));
+        assert!((&a - &a).is_zero());
+        assert!((&a * &Polynomial::zero()).is_zero());
+    }
+
+    #[test]
+    fn division_recombines() {
+        let mut rng = ark_std::test_rng();
+        let a = random_poly(9, &mut rng);
+        let b = random_poly(4, &mut rng);
+
+        let remainder = &a % &b;
+        assert!(remainder.coeffs().len() < b.coeffs().len());
+        assert_eq!(&(&(&a / &b) * &b) + &remainder, a);
+        assert_eq!(a.clone() / b.clone(), &a / &b);
+        assert_eq!(a.div_rem(&Polynomial::zero()).unwrap_err(), "Cannot divide by zero polynomial");
+    }
+
+    #[test]
+    fn from_roots_vanishes_on_the_roots() {
+        let mut rng = ark_std::test_rng();
+        let roots: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let poly = Polynomial::from_roots(&roots);
+        assert_eq!(poly.degree(), roots.len());
+        assert_eq!(poly.coeffs().last(), Some(&Fr::from(1u64)));
+        for root in &roots {
+            assert_eq!(poly.evaluate(*root), Fr::from(0u64));
+        }
+    }
+
+    #[test]
+    fn from_evaluations_goes_through_the_points() {
+        let mut rng = ark_std::test_rng();
+        let poly = random_poly(6, &mut rng);
+        let points: Vec<Fr> = (0..6).map(|_| Fr::rand(&mut rng)).collect();
+        let values: Vec<Fr> = points.iter().map(|&point| poly.evaluate(point)).collect();
+        assert_eq!(Polynomial::from_evaluations(&points, &values).unwrap(), poly);
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let mut rng = ark_std::test_rng();
+        let poly = random_poly(7, &mut rng);
+        for compress in [Compress::Yes, Compress::No] {
+            let mut bytes = Vec::new();
+            poly.serialize_with_mode(&mut bytes, compress).unwrap();
+            assert_eq!(bytes.len(), poly.serialized_size(compress));
+            let read = Polynomial::<Fr>::deserialize_with_mode(&bytes[..], compress, Validate::Yes).unwrap();
+            assert_eq!(read, poly);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+
+// Fiat-Shamir transcript: every message the prover has fixed is absorbed before a
+// challenge is squeezed, so the challenge cannot be known when the messages are chosen
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Self { hasher: Sha256::new() };
+        transcript.append_bytes(b"dom-sep", label);
+        transcript
+    }
+
+    pub fn append_bytes(&mut self, label: &'static [u8], bytes: &[u8]) {
+        // length prefixes keep the encoding of the absorbed messages unambiguous
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update((bytes.len() as u64).to_le_bytes());
+        self.hasher.update(bytes);
+    }
+
+    pub fn append<T: CanonicalSerialize + ?Sized>(&mut self, label: &'static [u8], message: &T) {
+        let mut bytes = Vec::new();
+        message
+            .serialize_uncompressed(&mut bytes)
+            .expect("Serialization failed");
+        self.append_bytes(label, &bytes);
+    }
+
+    // derive a field element from everything absorbed so far, the challenge
+    // itself is absorbed too so that consecutive challenges differ
+    pub fn challenge<F: PrimeField>(&mut self, label: &'static [u8]) -> F {
+        self.append_bytes(b"challenge", label);
+        let digest = self.hasher.clone().finalize();
+        self.append_bytes(b"challenge-output", &digest);
+        F::from_le_bytes_mod_order(&digest)
+    }
+}
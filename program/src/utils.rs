@@ -1,5 +1,10 @@
-use ark_ff::{Field, PrimeField};
+use ark_ff::{FftField, Field, PrimeField};
 use ark_std::log2;
+use crate::fft::Domain;
+
+// operands shorter than this are multiplied and divided with the schoolbook
+// algorithms, which beat the FFT based ones on small inputs
+const FFT_THRESHOLD: usize = 64;
 
 // helper function for polynomial addition
 pub fn add<E:Field>(p1: &[E], p2: &[E]) -> Vec<E> {
@@ -16,7 +21,15 @@ pub fn add<E:Field>(p1: &[E], p2: &[E]) -> Vec<E> {
 }
 
 // helper function for polynomial multiplication
-pub fn mul<E:Field>(p1: &[E], p2: &[E]) -> Vec<E> {
+pub fn mul<E:FftField>(p1: &[E], p2: &[E]) -> Vec<E> {
+    if std::cmp::min(p1.len(), p2.len()) < FFT_THRESHOLD {
+        return schoolbook_mul(p1, p2);
+    }
+    fft_mul(p1, p2).unwrap_or_else(|| schoolbook_mul(p1, p2))
+}
+
+// helper function for polynomial multiplication in O(n·m)
+pub fn schoolbook_mul<E:Field>(p1: &[E], p2: &[E]) -> Vec<E> {
     let mut result = vec![E::ZERO; p1.len() + p2.len() - 1];
 
     for (i, &coeff1) in p1.iter().enumerate() {
@@ -28,16 +41,54 @@ pub fn mul<E:Field>(p1: &[E], p2: &[E]) -> Vec<E> {
     result
 }
 
+// helper function for polynomial multiplication through the FFT in O(n log n),
+// returns None if the field has no root of unity of the required order
+pub fn fft_mul<E:FftField>(p1: &[E], p2: &[E]) -> Option<Vec<E>> {
+    let len = p1.len() + p2.len() - 1;
+    let domain = Domain::<E>::new(len)?;
+
+    let lhs = domain.fft(p1);
+    let rhs = domain.fft(p2);
+    let product: Vec<E> = lhs.iter().zip(rhs.iter()).map(|(&a, &b)| a * b).collect();
+
+    let mut result = domain.ifft(&product);
+    result.truncate(len);
+    Some(result)
+}
+
 // helper function for polynomial division
-pub fn div<E:Field>(p1: &[E], p2: &[E]) -> Result<Vec<E>, &'static str> {
-    if p2.is_empty() || p2.iter().all(|&x| x == E::ZERO) {
-        return Err("Cannot divide by zero polynomial");
-    }
+pub fn div<E:FftField>(p1: &[E], p2: &[E]) -> Result<Vec<E>, &'static str> {
+    div_rem(p1, p2).map(|(quotient, _)| quotient)
+}
+
+// helper function for polynomial division that also returns the remainder,
+// trailing zeros are stripped so an exact division yields an empty remainder
+pub fn div_rem<E:FftField>(p1: &[E], p2: &[E]) -> Result<(Vec<E>, Vec<E>), &'static str> {
+    // the divisions below need a non-zero leading coefficient
+    let degree = p2.iter().rposition(|&x| x != E::ZERO).ok_or("Cannot divide by zero polynomial")?;
+    let p2 = &p2[..degree + 1];
 
     if p1.len() < p2.len() {
-        return Ok(vec![E::ZERO]);
+        let mut remainder = p1.to_vec();
+        while let Some(true) = remainder.last().map(|x| *x == E::ZERO) {
+            remainder.pop();
+        }
+        return Ok((vec![E::ZERO], remainder));
+    }
+
+    let quotient_len = p1.len() - p2.len() + 1;
+    if std::cmp::min(p2.len(), quotient_len) >= FFT_THRESHOLD {
+        if let Some(result) = newton_div(p1, p2) {
+            return Ok(result);
+        }
     }
 
+    Ok(long_div(p1, p2))
+}
+
+// helper function for polynomial long division in O(n·m), returns the quotient
+// and the remainder, the divisor must not be the zero polynomial
+pub fn long_div<E:Field>(p1: &[E], p2: &[E]) -> (Vec<E>, Vec<E>) {
     let mut quotient = vec![E::ZERO; p1.len() - p2.len() + 1];
     let mut remainder: Vec<E> = p1.to_vec();
 
@@ -56,7 +107,58 @@ pub fn div<E:Field>(p1: &[E], p2: &[E]) -> Result<Vec<E>, &'static str> {
         }
     }
 
-    Ok(quotient)
+    (quotient, remainder)
+}
+
+// helper function for polynomial division through Newton iteration in O(n log n),
+// returns the quotient and the remainder, the divisor's leading coefficient must be non-zero
+pub fn newton_div<E:FftField>(p1: &[E], p2: &[E]) -> Option<(Vec<E>, Vec<E>)> {
+    let quotient_len = p1.len() - p2.len() + 1;
+
+    // reversing the coefficients turns the division into a multiplication
+    // by the inverse of the reversed divisor as a power series
+    let rev_p1: Vec<E> = p1.iter().rev().take(quotient_len).cloned().collect();
+    let rev_p2: Vec<E> = p2.iter().rev().cloned().collect();
+    let rev_p2_inv = power_series_inverse(&rev_p2, quotient_len)?;
+
+    let mut quotient = mul(&rev_p1, &rev_p2_inv);
+    quotient.truncate(quotient_len);
+    quotient.reverse();
+
+    // only the low coefficients of p1 - quotient * p2 can be non-zero
+    let product = mul(&quotient, p2);
+    let mut remainder: Vec<E> = p1
+        .iter()
+        .zip(product.iter())
+        .take(p2.len() - 1)
+        .map(|(&a, &b)| a - b)
+        .collect();
+    while let Some(true) = remainder.last().map(|x| *x == E::ZERO) {
+        remainder.pop();
+    }
+
+    Some((quotient, remainder))
+}
+
+// helper function to invert a power series modulo x^len, doubling the precision
+// on every Newton step: inv <- inv * (2 - poly * inv)
+fn power_series_inverse<E:FftField>(poly: &[E], len: usize) -> Option<Vec<E>> {
+    let mut inv = vec![poly[0].inverse()?];
+    let mut precision = 1;
+
+    while precision < len {
+        precision = std::cmp::min(2 * precision, len);
+
+        let truncated = &poly[..std::cmp::min(poly.len(), precision)];
+        let mut correction: Vec<E> = mul(truncated, &inv).iter().map(|&x| -x).collect();
+        correction.truncate(precision);
+        correction[0] += E::from(2u64);
+
+        inv = mul(&inv, &correction);
+        inv.truncate(precision);
+    }
+
+    Some(inv)
 }
 
 // helper function to evaluate polynomial at a point
@@ -70,31 +172,39 @@ pub fn evaluate<E:Field>(poly: &[E], point: E) -> E {
     value
 }
 
-// helper function to perform Lagrange interpolation given a set of points
-pub fn interpolate<E:Field>(points: &[E], values: &[E]) -> Result<Vec<E>, &'static str> {
+// helper function to perform Lagrange interpolation given a set of points in O(k^2):
+// the zero polynomial of all points is built once and each basis polynomial is
+// divided out of it rather than rebuilt from the other k - 1 roots
+pub fn interpolate<E:FftField>(points: &[E], values: &[E]) -> Result<Vec<E>, &'static str> {
     if points.len() != values.len() {
         return Err("Number of points and values do not match");
     }
 
-    let mut result = vec![E::ZERO; points.len()];
-
-    for i in 0..points.len() {
-        let mut numerator = vec![E::ONE];
-        let mut denominator = E::ONE;
-
-        for j in 0..points.len() {
-            if i == j {
-                continue;
-            }
+    let mut zero_poly = vec![E::ONE];
+    for &point in points {
+        zero_poly = mul(&zero_poly, &[-point, E::ONE]);
+    }
 
-            numerator = mul(&numerator, &[-points[j], E::ONE]);
-            denominator *= points[i] - points[j];
+    let mut result = vec![E::ZERO; points.len()];
+    let mut basis = vec![E::ZERO; points.len()];
+    for (i, (&point, &value)) in points.iter().zip(values).enumerate() {
+        // zero_poly / (x - point) by synthetic division, the remainder is zero
+        let mut carry = E::ZERO;
+        for k in (1..zero_poly.len()).rev() {
+            carry = zero_poly[k] + carry * point;
+            basis[k - 1] = carry;
         }
 
-        let denominator_inv = denominator.inverse().unwrap();
-        let term: Vec<E> = numerator.iter().map(|&x| x * values[i] * denominator_inv).collect();
-
-        result = add(&result, &term);
+        let denominator: E = points
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, &other)| point - other)
+            .product();
+        let scale = value * denominator.inverse().ok_or("Points must be distinct")?;
+        for (coeff, &b) in result.iter_mut().zip(basis.iter()) {
+            *coeff += b * scale;
+        }
     }
 
     Ok(result)
@@ -128,4 +238,89 @@ pub fn scalar_mul<E:Field>(poly: &[E], scalar: E) -> Vec<E> {
         result.push(*coeff * scalar);
     }
     result    
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_std::UniformRand;
+
+    fn random_poly(len: usize) -> Vec<Fr> {
+        let mut rng = ark_std::test_rng();
+        (0..len).map(|_| Fr::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn mul_matches_schoolbook() {
+        for (n, m) in [(1, 1), (63, 64), (64, 64), (100, 70), (513, 129)] {
+            let p1 = random_poly(n);
+            let p2 = random_poly(m);
+            assert_eq!(mul(&p1, &p2), schoolbook_mul(&p1, &p2), "sizes ({}, {})", n, m);
+        }
+    }
+
+    #[test]
+    fn div_rem_matches_long_div() {
+        for (n, m) in [(64, 64), (127, 64), (100, 70), (513, 129)] {
+            let p1 = random_poly(n);
+            let p2 = random_poly(m);
+            let expected = long_div(&p1, &p2);
+            assert_eq!(div_rem(&p1, &p2).unwrap(), expected, "sizes ({}, {})", n, m);
+            if n - m + 1 >= FFT_THRESHOLD {
+                assert_eq!(newton_div(&p1, &p2).unwrap(), expected, "sizes ({}, {})", n, m);
+            }
+        }
+    }
+
+    #[test]
+    fn exact_division_has_empty_remainder() {
+        let quotient = random_poly(200);
+        let divisor = random_poly(100);
+        let product = mul(&quotient, &divisor);
+
+        let (q, r) = div_rem(&product, &divisor).unwrap();
+        assert_eq!(q, quotient);
+        assert!(r.is_empty());
+        assert_eq!(newton_div(&product, &divisor).unwrap(), (quotient, vec![]));
+    }
+
+    #[test]
+    fn divisor_trailing_zeros_are_ignored() {
+        let p1 = random_poly(10);
+        let (q, r) = div_rem(&p1, &[Fr::from(1u64), Fr::from(0u64)]).unwrap();
+        assert_eq!(q, p1);
+        assert!(r.is_empty());
+
+        let p2 = random_poly(100);
+        let mut padded = p2.clone();
+        padded.resize(130, Fr::from(0u64));
+        let p1 = random_poly(300);
+        assert_eq!(div_rem(&p1, &padded).unwrap(), div_rem(&p1, &p2).unwrap());
+
+        assert!(div_rem(&p1, &[Fr::from(0u64), Fr::from(0u64)]).is_err());
+        assert!(div_rem(&p1, &[]).is_err());
+    }
+
+    #[test]
+    fn interpolate_matches_the_lagrange_basis() {
+        let mut rng = ark_std::test_rng();
+        for k in [1, 2, 7, 40] {
+            let points: Vec<Fr> = (0..k).map(|_| Fr::rand(&mut rng)).collect();
+            let values: Vec<Fr> = (0..k).map(|_| Fr::rand(&mut rng)).collect();
+            let expected = crate::lagrange::LagrangeInterpContext::new_from_points(&points)
+                .unwrap()
+                .interpolate(&values)
+                .unwrap();
+            let poly = crate::polynomial::Polynomial::new(interpolate(&points, &values).unwrap());
+            assert_eq!(poly, expected, "{} points", k);
+        }
+    }
+
+    #[test]
+    fn interpolate_rejects_repeated_points() {
+        let points = [Fr::from(1u64), Fr::from(2u64), Fr::from(1u64)];
+        let values = [Fr::from(3u64), Fr::from(4u64), Fr::from(5u64)];
+        assert_eq!(interpolate(&points, &values).unwrap_err(), "Points must be distinct");
+        assert!(interpolate(&points, &values[..2]).is_err());
+    }
+}
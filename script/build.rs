@@ -1,5 +1,24 @@
-use sp1_helper::build_program;
+use sp1_build::{build_program_with_args, BuildArgs};
 
 fn main() {
-    build_program("../program")
+    // build the guest with the BLS12-381 precompiles when the host asks for them,
+    // the default build keeps the pure arkworks guest to cross-check against
+    let mut args = BuildArgs {
+        elf_name: Some("riscv32im-succinct-zkvm-elf".to_string()),
+        output_directory: Some("elf".to_string()),
+        ..Default::default()
+    };
+    if std::env::var("CARGO_FEATURE_PRECOMPILES").is_ok() {
+        args.features.push("precompiles".to_string());
+    }
+    build_program_with_args("../program", args);
+
+    // the BN254 guest goes next to it, the host picks one with the CURVE env var
+    let bn254_args = BuildArgs {
+        features: vec!["bn254".to_string()],
+        elf_name: Some("riscv32im-succinct-zkvm-elf-bn254".to_string()),
+        output_directory: Some("elf".to_string()),
+        ..Default::default()
+    };
+    build_program_with_args("../program", bn254_args)
 }